@@ -0,0 +1,38 @@
+#!/bin/sh
+# Hook script for odhcp6c, which writes the state of an interface as json for the agent.
+#
+#   odhcp6c -s /path/to/odhcp6c.sh <interface>
+#
+# The state directory must be mounted into the agent at DHCP_TEMPLATE__ODHCP6C_PATH.
+
+set -eu
+
+interface="$1"
+state="$2"
+dir="${DHCP_TEMPLATE__ODHCP6C_PATH:-/run/dhcp-template/odhcp6c}"
+file="$dir/$interface.json"
+
+json_string() {
+    printf '"%s"' "$(printf '%s' "$1" | sed -e 's/\\/\\\\/g' -e 's/"/\\"/g')"
+}
+
+case "$state" in
+    unbound | stopped)
+        rm -f "$file"
+        ;;
+    *)
+        mkdir -p "$dir"
+        tmp="$(mktemp "$dir/.$interface.XXXXXX")"
+        {
+            printf '{"interface":%s,' "$(json_string "$interface")"
+            printf '"state":%s,' "$(json_string "$state")"
+            printf '"prefixes":%s,' "$(json_string "${PREFIXES:-}")"
+            printf '"rdnss":%s,' "$(json_string "${RDNSS:-}")"
+            printf '"addresses":%s,' "$(json_string "${ADDRESSES:-}")"
+            printf '"domains":%s,' "$(json_string "${DOMAINS:-}")"
+            printf '"ntp_ip":%s,' "$(json_string "${NTP_IP:-}")"
+            printf '"ntp_fqdn":%s}\n' "$(json_string "${NTP_FQDN:-}")"
+        } >"$tmp"
+        mv "$tmp" "$file"
+        ;;
+esac
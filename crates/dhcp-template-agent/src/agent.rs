@@ -1,18 +1,27 @@
-use std::time::Duration;
+use std::{cmp::min, future::pending, time::Duration};
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Result, anyhow, bail};
 use dhcp_template_api::{
-    Interface, Node, Refresh, Scope, Update,
+    Node, Scope, Shallow, Update,
     controller_service_client::ControllerServiceClient,
     update::Data::{self},
 };
 use envconfig::Envconfig;
 use futures_util::{Stream, TryStreamExt};
-use tokio::{select, time::sleep};
-use tonic::{Request, transport::Uri};
+use prost::Message as _;
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    sync::{mpsc, watch},
+    time::{Instant, sleep, sleep_until, timeout},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Channel, Endpoint, Uri};
 use tracing::{Level, debug, instrument};
 
-use crate::{provider::Provider, shallow::ShallowClone as _};
+use crate::{delta::DeltaClone as _, provider::Provider, shallow::ShallowClone as _};
+
+const DEREGISTER_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Envconfig)]
 pub struct Config {
@@ -21,18 +30,32 @@ pub struct Config {
 
     #[envconfig(from = "DHCP_TEMPLATE__ENDPOINT", default = "http://[::1]:50051")]
     endpoint: Uri,
+
+    #[envconfig(from = "DHCP_TEMPLATE__RETRY_MIN_SECONDS", default = "1")]
+    retry_min_seconds: u64,
+
+    #[envconfig(from = "DHCP_TEMPLATE__RETRY_MAX_SECONDS", default = "60")]
+    retry_max_seconds: u64,
 }
 
 pub struct Agent {
     node_name: String,
-    endpoint: Uri,
+    client: ControllerServiceClient<Channel>,
+    retry_min: Duration,
+    retry_max: Duration,
 }
 
 impl From<Config> for Agent {
     fn from(config: Config) -> Self {
+        // The channel connects on the first request and reconnects whenever the connection is lost,
+        // so the operator does not need to be available when the agent starts.
+        let channel = Endpoint::from(config.endpoint).connect_lazy();
+
         Self {
             node_name: config.node_name.unwrap_or_else(random_node_name),
-            endpoint: config.endpoint,
+            client: ControllerServiceClient::new(channel),
+            retry_min: Duration::from_secs(config.retry_min_seconds),
+            retry_max: Duration::from_secs(config.retry_max_seconds),
         }
     }
 }
@@ -43,77 +66,203 @@ fn random_node_name() -> String {
     format!("node-{:016x}", r)
 }
 
+async fn forward(
+    mut updates: impl Stream<Item = Result<Update>> + Unpin,
+    latest: watch::Sender<Update>,
+) -> Result<()> {
+    while let Some(update) = updates.try_next().await? {
+        latest.send_replace(update);
+    }
+
+    bail!("Provider closed!");
+}
+
+// The token only changes with the content of the node, so a restarted agent with the same leases
+// does not deprecate the state known to the controller. Maps are encoded in order, so the encoding
+// is stable, and it is hashed with FNV-1a, which unlike the hasher of the standard library does not
+// change between releases of Rust.
+fn content_token(node: &Node) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    node.encode_to_vec()
+        .iter()
+        .fold(OFFSET_BASIS, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
+
+#[instrument(ret(level = Level::INFO), err(level = Level::WARN))]
+async fn terminate() -> Result<()> {
+    signal(SignalKind::terminate())?.recv().await;
+
+    Ok(())
+}
+
+async fn sleep_until_some(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => sleep_until(deadline).await,
+        None => pending().await,
+    }
+}
+
 impl Agent {
     #[instrument(skip_all, fields(node = self.node_name))]
     pub async fn run(&self, provider: Box<dyn Provider>) -> Result<()> {
         let mut updates = self.get_updates(&*provider);
 
-        let mut refresh = Refresh::default();
-        let mut update = updates
+        let update = updates
             .try_next()
             .await?
             .ok_or_else(|| anyhow!("Could not get initial node state."))?;
 
+        // Only the latest update is kept, while the controller is unavailable.
+        let (latest, receiver) = watch::channel(update);
+
+        select! {
+            result = forward(updates, latest) => return result,
+            result = self.connect(receiver) => return result,
+            result = terminate() => result?,
+        }
+
+        // Failing to deregister only delays the removal until the session times out.
+        let _ = timeout(DEREGISTER_TIMEOUT, self.deregister()).await;
+
+        Ok(())
+    }
+
+    async fn connect(&self, mut latest: watch::Receiver<Update>) -> Result<()> {
+        let mut failures = 0;
+
         loop {
-            refresh = self.push_node(&update, refresh.scope()).await?;
-
-            select! {
-               () = sleep(Duration::from_secs(refresh.backoff_seconds)) => {
-                   debug!("Backoff duration has passed.");
-               }
-
-               result = updates.try_next() => {
-                   match result? {
-                       Some(next) => {
-                           debug!("Interfaces changed.");
-                           update = next;
-                           refresh.set_scope(Scope::Full);
-                       },
-                       None => {
-                           bail!("Provider closed!");
-                       },
-                   }
-               }
+            if self.session(&mut latest, &mut failures).await.is_err() {
+                failures += 1;
+                sleep(self.retry_backoff(failures)).await;
             }
         }
     }
 
-    #[instrument(
-        skip(self, update),
-        fields(token = update.token),
-        ret(level = Level::DEBUG),
-        err(level = Level::WARN),
-    )]
-    async fn push_node(&self, update: &Update, scope: Scope) -> Result<Refresh> {
-        let mut controller_service = ControllerServiceClient::connect(self.endpoint.clone())
-            .await
-            .context("Could not connect to controller.")?;
-
-        let request = Request::new(match scope {
-            Scope::Shallow => update.shallow_clone(),
-            Scope::Full => update.clone(),
-        });
-
-        let response = controller_service.push_node(request).await?;
-        let refresh = response.into_inner();
-
-        Ok(refresh)
+    // Pushes the full state of the node when the session starts and when the controller asks for it,
+    // and only the changed interfaces whenever it changes. In between, shallow updates keep the node
+    // alive.
+    #[instrument(skip_all, err(level = Level::WARN))]
+    async fn session(
+        &self,
+        latest: &mut watch::Receiver<Update>,
+        failures: &mut u32,
+    ) -> Result<()> {
+        // The last state pushed in this session, which deltas are based on.
+        let mut base = latest.borrow_and_update().clone();
+
+        let (sender, receiver) = mpsc::channel(1);
+        sender.send(base.clone()).await?;
+
+        let response = self
+            .client
+            .clone()
+            .node_session(ReceiverStream::new(receiver))
+            .await?;
+        let mut refreshes = response.into_inner();
+        let mut keepalive = None;
+
+        loop {
+            let update = select! {
+                refresh = refreshes.message() => {
+                    let refresh = refresh?.ok_or_else(|| anyhow!("Controller closed the session."))?;
+                    debug!(?refresh, "Received refresh.");
+                    *failures = 0;
+
+                    match refresh.scope() {
+                        Scope::Full => {
+                            base = latest.borrow_and_update().clone();
+                            base.clone()
+                        }
+                        Scope::Shallow => {
+                            let backoff = Duration::from_secs(refresh.backoff_seconds);
+                            keepalive = Some(Instant::now() + backoff);
+                            continue;
+                        }
+                    }
+                }
+
+                () = sleep_until_some(keepalive) => {
+                    debug!("Backoff duration has passed.");
+                    base.shallow_clone()
+                }
+
+                changed = latest.changed() => {
+                    changed?;
+                    debug!("Interfaces changed.");
+                    let next = latest.borrow_and_update().clone();
+                    let delta = next.delta_clone(&base);
+                    base = next;
+                    delta
+                }
+            };
+
+            keepalive = None;
+            sender.send(update).await?;
+        }
+    }
+
+    #[instrument(skip(self), ret(level = Level::DEBUG), err(level = Level::WARN))]
+    async fn deregister(&self) -> Result<()> {
+        let shallow = Shallow {
+            name: self.node_name.clone(),
+        };
+
+        self.client.clone().deregister(shallow).await?;
+
+        Ok(())
+    }
+
+    // Exponential backoff between failed sessions, with a random jitter of up to half the duration, so
+    // agents do not reconnect in lockstep after an operator restart.
+    #[instrument(skip(self), ret(level = Level::DEBUG))]
+    fn retry_backoff(&self, failures: u32) -> Duration {
+        let backoff = self
+            .retry_min
+            .saturating_mul(2_u32.saturating_pow(failures.saturating_sub(1)));
+        let backoff = min(backoff, self.retry_max);
+
+        backoff.mul_f64(1.0 - rand::random::<f64>() / 2.0)
     }
 
     fn get_updates(&self, provider: &dyn Provider) -> impl Stream<Item = Result<Update>> {
-        provider
-            .interfaces()
-            .map_ok(|interfaces| self.map_update(interfaces))
+        provider.node().map_ok(|node| self.map_update(node))
     }
 
     #[instrument(skip_all, ret(level = Level::INFO))]
-    fn map_update(&self, interfaces: Vec<Interface>) -> Update {
+    fn map_update(&self, node: Node) -> Update {
+        let mut node = Node {
+            name: self.node_name.clone(),
+            ..node
+        };
+
+        // Interfaces are ordered by name, like the controller orders patched interfaces.
+        node.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
         Update {
-            token: rand::random(),
-            data: Some(Data::Full(Node {
-                name: self.node_name.clone(),
-                interfaces,
-            })),
+            token: content_token(&node),
+            data: Some(Data::Full(node)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use dhcp_template_api::Node;
+
+    use crate::agent::content_token;
+
+    // Tokens are compared across restarts and releases, so they must never change.
+    #[test]
+    fn content_token_is_stable() {
+        let node = Node {
+            name: "node1".to_owned(),
+            ..Default::default()
+        };
+
+        assert_eq!(content_token(&node), 6_386_663_273_363_746_751);
+    }
+}
@@ -0,0 +1,41 @@
+use dhcp_template_api::{Delta, Update, update::Data};
+
+pub trait DeltaClone {
+    fn delta_clone(&self, base: &Self) -> Self;
+}
+
+impl DeltaClone for Update {
+    fn delta_clone(&self, base: &Self) -> Self {
+        match (&self.data, &base.data) {
+            (Some(Data::Full(node)), Some(Data::Full(base_node)))
+                if node.name == base_node.name =>
+            {
+                let interfaces = node
+                    .interfaces
+                    .iter()
+                    .filter(|interface| !base_node.interfaces.contains(interface))
+                    .cloned()
+                    .collect();
+
+                let removed = base_node
+                    .interfaces
+                    .iter()
+                    .filter(|base| !node.interfaces.iter().any(|i| i.name == base.name))
+                    .map(|base| base.name.clone())
+                    .collect();
+
+                Update {
+                    token: self.token,
+                    data: Some(Data::Delta(Delta {
+                        name: node.name.clone(),
+                        base_token: base.token,
+                        interfaces,
+                        removed,
+                        served: node.served.clone(),
+                    })),
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod agent;
+mod delta;
 mod provider;
 mod shallow;
 
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_stream::try_stream;
+use dhcp_template_api::{Interface, Node};
+use futures_util::{
+    StreamExt as _, TryStreamExt as _,
+    stream::{BoxStream, select_all},
+};
+
+use crate::provider::Provider;
+
+// Merges the nodes reported by several providers. Every report is emitted with the latest reports of
+// the other providers, so a provider that has not reported yet does not hold back the others.
+#[derive(Debug)]
+pub struct CompositeProvider {
+    providers: Vec<Box<dyn Provider>>,
+}
+
+impl From<Vec<Box<dyn Provider>>> for CompositeProvider {
+    fn from(providers: Vec<Box<dyn Provider>>) -> Self {
+        Self { providers }
+    }
+}
+
+impl Provider for CompositeProvider {
+    fn node(&self) -> BoxStream<'_, Result<Node>> {
+        let mut updates = select_all(
+            self.providers
+                .iter()
+                .enumerate()
+                .map(|(index, provider)| provider.node().map_ok(move |node| (index, node))),
+        );
+
+        let mut latest = vec![None; self.providers.len()];
+
+        try_stream! {
+            while let Some((index, node)) = updates.try_next().await? {
+                latest[index] = Some(node);
+                yield merge(latest.iter().flatten().cloned());
+            }
+        }
+        .boxed()
+    }
+}
+
+// Fields of an interface are taken from the first provider that reports them. Served leases of all
+// providers are kept.
+fn merge(all: impl Iterator<Item = Node>) -> Node {
+    let mut merged: BTreeMap<String, Interface> = BTreeMap::new();
+    let mut served = Vec::new();
+
+    for node in all {
+        served.extend(node.served);
+        merge_interfaces(&mut merged, node.interfaces);
+    }
+
+    Node {
+        interfaces: merged.into_values().collect(),
+        served,
+        ..Default::default()
+    }
+}
+
+fn merge_interfaces(merged: &mut BTreeMap<String, Interface>, interfaces: Vec<Interface>) {
+    for other in interfaces {
+        let Some(interface) = merged.get_mut(&other.name) else {
+            merged.insert(other.name.clone(), other);
+            continue;
+        };
+
+        if interface.lease4.is_none() {
+            interface.lease4 = other.lease4;
+        }
+
+        if interface.lease6.is_none() {
+            interface.lease6 = other.lease6;
+        }
+
+        if interface.addresses.is_empty() {
+            interface.addresses = other.addresses;
+        }
+
+        if interface.routes.is_empty() {
+            interface.routes = other.routes;
+        }
+
+        interface.mac = interface.mac.take().or(other.mac);
+        interface.mtu = interface.mtu.or(other.mtu);
+        interface.operstate = interface.operstate.take().or(other.operstate);
+        interface.ifindex = interface.ifindex.or(other.ifindex);
+    }
+}
@@ -0,0 +1,670 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, canonicalize, read_to_string},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use dhcp_template_api::Interface;
+use envconfig::Envconfig;
+use tracing::{Level, instrument};
+
+use crate::provider::notify::{InterfaceReader, NotifyProvider};
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(from = "DHCP_TEMPLATE__DHCLIENT_PATH", default = "/var/lib/dhcp")]
+    path: PathBuf,
+}
+
+pub type DhclientProvider = NotifyProvider<DhclientInterfaceReader>;
+
+impl TryFrom<Config> for DhclientProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let path = canonicalize(config.path)?;
+        let provider = Self::new(path, DhclientInterfaceReader);
+
+        Ok(provider)
+    }
+}
+
+#[derive(Debug)]
+pub struct DhclientInterfaceReader;
+
+#[async_trait]
+impl InterfaceReader for DhclientInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn interfaces(&self, path: &Path) -> Result<Vec<Interface>> {
+        let lease_files = fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_lease_file(path));
+
+        let mut interfaces = BTreeMap::new();
+
+        for lease_file in lease_files {
+            merge_interfaces(&mut interfaces, parse_lease_file(&lease_file)?);
+        }
+
+        Ok(interfaces.into_values().collect())
+    }
+}
+
+fn is_lease_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| name.starts_with("dhclient") && name.ends_with(".leases"))
+}
+
+// Lease files are read in directory order, so keep whichever lease was acquired last.
+pub fn merge_interfaces(interfaces: &mut BTreeMap<String, Interface>, other: Vec<Interface>) {
+    for other in other {
+        let interface = interfaces.entry(other.name.clone()).or_insert(Interface {
+            name: other.name,
+            ..Default::default()
+        });
+
+        if let Some(lease4) = other.lease4
+            && interface.lease4.as_ref().is_none_or(|current| {
+                (current.acquired_at, current.expires_at) <= (lease4.acquired_at, lease4.expires_at)
+            })
+        {
+            interface.lease4 = Some(lease4);
+        }
+
+        if let Some(lease6) = other.lease6
+            && interface.lease6.as_ref().is_none_or(|current| {
+                (current.acquired_at, current.expires_at) <= (lease6.acquired_at, lease6.expires_at)
+            })
+        {
+            interface.lease6 = Some(lease6);
+        }
+    }
+}
+
+// dhclient appends every renewed lease to the file, so later blocks supersede earlier ones.
+// Leases that have already expired are skipped.
+#[instrument(ret(level = Level::DEBUG), err(level = Level::WARN))]
+pub fn parse_lease_file(path: &Path) -> Result<Vec<Interface>> {
+    let content = read_to_string(path)?;
+    let statements = grammar::parse(&content)
+        .with_context(|| format!("Could not parse dhclient leases {}.", path.display()))?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut interfaces = BTreeMap::new();
+
+    for statement in &statements {
+        let lease = match statement.key() {
+            Some("lease") => lease::v4(&statement.block),
+            Some("lease6") => lease::v6(&statement.block),
+            _ => continue,
+        };
+
+        let Some(lease) = lease.filter(|lease| lease.expires.is_none_or(|expires| expires > now))
+        else {
+            continue;
+        };
+
+        let interface = interfaces
+            .entry(lease.interface.clone())
+            .or_insert(Interface {
+                name: lease.interface,
+                ..Default::default()
+            });
+
+        match lease.data {
+            lease::Data::V4(lease4) => interface.lease4 = Some(lease4),
+            lease::Data::V6(lease6) => interface.lease6 = Some(lease6),
+        }
+    }
+
+    Ok(interfaces.into_values().collect())
+}
+
+mod lease {
+    use std::{collections::BTreeMap, net::Ipv4Addr};
+
+    use dhcp_template_api::{Address6, Lease4, Lease6, Prefix6, Route};
+
+    use crate::provider::{
+        dhclient::grammar::Statement,
+        dhcpcd::{hex, v4::parse_classless_routes},
+    };
+
+    pub struct Lease {
+        pub interface: String,
+        pub expires: Option<u64>,
+        pub data: Data,
+    }
+
+    pub enum Data {
+        V4(Lease4),
+        V6(Lease6),
+    }
+
+    // dhclient writes options by name, which are mapped back to their codes. Options it does not know
+    // are written as `unknown-<code>`.
+    const OPTIONS4: [(&str, u32); 16] = [
+        ("subnet-mask", 1),
+        ("routers", 3),
+        ("domain-name-servers", 6),
+        ("host-name", 12),
+        ("domain-name", 15),
+        ("interface-mtu", 26),
+        ("broadcast-address", 28),
+        ("ntp-servers", 42),
+        ("dhcp-lease-time", 51),
+        ("dhcp-message-type", 53),
+        ("dhcp-server-identifier", 54),
+        ("dhcp-renewal-time", 58),
+        ("dhcp-rebinding-time", 59),
+        ("domain-search", 119),
+        ("rfc3442-classless-static-routes", 121),
+        ("ms-classless-static-routes", 249),
+    ];
+
+    const OPTIONS6: [(&str, u32); 4] = [
+        ("dhcp6.client-id", 1),
+        ("dhcp6.server-id", 2),
+        ("dhcp6.name-servers", 23),
+        ("dhcp6.domain-search", 24),
+    ];
+
+    pub fn v4(block: &[Statement]) -> Option<Lease> {
+        let interface = interface(block)?;
+        let expires = block
+            .iter()
+            .find(|statement| statement.key() == Some("expire"))
+            .and_then(|statement| parse_date(&statement.words[1..]));
+
+        let lease_time_seconds = option(block, "dhcp-lease-time").and_then(|v| v.parse().ok());
+
+        let lease4 = Lease4 {
+            dns: option_list(block, "domain-name-servers"),
+            domain: option(block, "domain-name").map(ToOwned::to_owned),
+            address: block
+                .iter()
+                .find(|statement| statement.key() == Some("fixed-address"))
+                .and_then(|statement| statement.words.get(1))
+                .cloned(),
+            prefix_len: option(block, "subnet-mask")
+                .and_then(|mask| mask.parse::<Ipv4Addr>().ok())
+                .map(|mask| u32::from(mask).leading_ones()),
+            routers: option_list(block, "routers"),
+            server_id: option(block, "dhcp-server-identifier").map(ToOwned::to_owned),
+            lease_time_seconds,
+            renewal_time_seconds: option(block, "dhcp-renewal-time").and_then(|v| v.parse().ok()),
+            rebinding_time_seconds: option(block, "dhcp-rebinding-time")
+                .and_then(|v| v.parse().ok()),
+            domain_search: domain_list(block, "domain-search"),
+            ntp: option_list(block, "ntp-servers"),
+            mtu: option(block, "interface-mtu").and_then(|v| v.parse().ok()),
+            routes: classless_routes(block, "rfc3442-classless-static-routes")
+                .or_else(|| classless_routes(block, "ms-classless-static-routes"))
+                .unwrap_or_default(),
+            // dhclient only records the expiry, the lease was acquired one lease time earlier.
+            acquired_at: expires
+                .zip(lease_time_seconds)
+                .map(|(expires, lease_time)| expires.saturating_sub(u64::from(lease_time))),
+            expires_at: expires,
+            options: options(block, &OPTIONS4, "unknown-"),
+        };
+
+        Some(Lease {
+            interface,
+            expires,
+            data: Data::V4(lease4),
+        })
+    }
+
+    pub fn v6(block: &[Statement]) -> Option<Lease> {
+        let interface = interface(block)?;
+        let mut acquired_at = None;
+        let mut expires = None;
+        let mut prefix6 = Vec::new();
+        let mut address6 = Vec::new();
+
+        for ia in block
+            .iter()
+            .filter(|ia| matches!(ia.key(), Some("ia-pd" | "ia-na")))
+        {
+            let ia_starts = number(&ia.block, "starts");
+            let renewal_time_seconds = seconds(&ia.block, "renew");
+            let rebinding_time_seconds = seconds(&ia.block, "rebind");
+            let iaid = ia
+                .words
+                .get(1)
+                .and_then(|iaid| hex_bytes(iaid))
+                .and_then(|iaid| Some(u32::from_be_bytes(iaid.try_into().ok()?)));
+
+            for ia_entry in ia
+                .block
+                .iter()
+                .filter(|entry| matches!(entry.key(), Some("iaprefix" | "iaaddr")))
+            {
+                let starts = number(&ia_entry.block, "starts").or(ia_starts);
+                let max_life = number(&ia_entry.block, "max-life");
+
+                if let Some(starts) = starts {
+                    acquired_at = Some(
+                        acquired_at.map_or(starts, |acquired_at: u64| acquired_at.min(starts)),
+                    );
+                }
+
+                if let (Some(starts), Some(max_life)) = (starts, max_life) {
+                    expires = expires.max(Some(starts.saturating_add(max_life)));
+                }
+
+                let Some(ip) = ia_entry.words.get(1) else {
+                    continue;
+                };
+
+                let preferred_lifetime_seconds = seconds(&ia_entry.block, "preferred-life");
+                let valid_lifetime_seconds = seconds(&ia_entry.block, "max-life");
+
+                if ia_entry.key() == Some("iaaddr") {
+                    address6.push(Address6 {
+                        ip: ip.clone(),
+                        preferred_lifetime_seconds,
+                        valid_lifetime_seconds,
+                        renewal_time_seconds,
+                        rebinding_time_seconds,
+                        iaid,
+                    });
+
+                    continue;
+                }
+
+                let Some((ip, len)) = ip.split_once('/') else {
+                    continue;
+                };
+
+                let Ok(len) = len.parse() else {
+                    continue;
+                };
+
+                prefix6.push(Prefix6 {
+                    ip: ip.to_owned(),
+                    len,
+                    preferred_lifetime_seconds,
+                    valid_lifetime_seconds,
+                    renewal_time_seconds,
+                    rebinding_time_seconds,
+                    iaid,
+                });
+            }
+        }
+
+        let lease6 = Lease6 {
+            dns: option_list(block, "dhcp6.name-servers"),
+            prefix6,
+            address6,
+            domain_search: domain_list(block, "dhcp6.domain-search"),
+            acquired_at,
+            expires_at: expires,
+            server_duid: duid(block, "dhcp6.server-id"),
+            client_duid: duid(block, "dhcp6.client-id"),
+            options: options(block, &OPTIONS6, "dhcp6.unknown-"),
+            ..Default::default()
+        };
+
+        Some(Lease {
+            interface,
+            expires,
+            data: Data::V6(lease6),
+        })
+    }
+
+    fn interface(block: &[Statement]) -> Option<String> {
+        block
+            .iter()
+            .find(|statement| statement.key() == Some("interface"))
+            .and_then(|statement| statement.words.get(1))
+            .cloned()
+    }
+
+    fn number(block: &[Statement], key: &str) -> Option<u64> {
+        block
+            .iter()
+            .find(|statement| statement.key() == Some(key))
+            .and_then(|statement| statement.words.get(1))
+            .and_then(|value| value.parse().ok())
+    }
+
+    fn seconds(block: &[Statement], key: &str) -> Option<u32> {
+        number(block, key).and_then(|value| u32::try_from(value).ok())
+    }
+
+    fn option<'a>(block: &'a [Statement], name: &str) -> Option<&'a str> {
+        option_words(block, name)?.first().map(String::as_str)
+    }
+
+    // Lists of quoted strings like `"a.example", "b.example"` are split into several words.
+    fn option_list(block: &[Statement], name: &str) -> Vec<String> {
+        option_words(block, name)
+            .map(|words| {
+                words
+                    .concat()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Search domains are written fully qualified, with a trailing dot.
+    fn domain_list(block: &[Statement], name: &str) -> Vec<String> {
+        option_list(block, name)
+            .into_iter()
+            .map(|domain| domain.trim_end_matches('.').to_owned())
+            .collect()
+    }
+
+    // Identifiers like DUIDs and IAIDs are written as colon separated hex bytes.
+    fn hex_bytes(value: &str) -> Option<Vec<u8>> {
+        value
+            .split(':')
+            .map(|byte| u8::from_str_radix(byte, 16).ok())
+            .collect()
+    }
+
+    fn duid(block: &[Statement], name: &str) -> Option<String> {
+        option(block, name)
+            .and_then(hex_bytes)
+            .map(|duid| hex(&duid))
+    }
+
+    // Routes are written as the comma separated bytes of the option.
+    fn classless_routes(block: &[Statement], name: &str) -> Option<Vec<Route>> {
+        let bytes = option_list(block, name)
+            .iter()
+            .map(|byte| byte.parse().ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        (!bytes.is_empty())
+            .then(|| parse_classless_routes(&bytes))
+            .flatten()
+    }
+
+    // Values are rendered like the options decoded by dhcpcd, as comma separated text for known
+    // options and as hex bytes for unknown ones.
+    fn options(block: &[Statement], known: &[(&str, u32)], unknown: &str) -> BTreeMap<u32, String> {
+        block
+            .iter()
+            .filter(|statement| statement.key() == Some("option"))
+            .filter_map(|statement| {
+                let name = statement.words.get(1)?;
+                let value = statement.words.get(2..)?.concat();
+
+                if let Some(code) = name.strip_prefix(unknown) {
+                    return Some((code.parse().ok()?, hex_value(&value)));
+                }
+
+                let (_, code) = known.iter().find(|(known, _)| known == name)?;
+                let value = match name.as_str() {
+                    "rfc3442-classless-static-routes" | "ms-classless-static-routes" => {
+                        classless_routes(block, name)?
+                            .iter()
+                            .map(|route| {
+                                format!(
+                                    "{}/{} {}",
+                                    route.destination,
+                                    route.prefix_len,
+                                    route.gateway.as_deref().unwrap_or("0.0.0.0")
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    }
+                    "domain-search" | "dhcp6.domain-search" => domain_list(block, name).join(","),
+                    "dhcp6.client-id" | "dhcp6.server-id" => hex_value(&value),
+                    _ => value,
+                };
+
+                Some((*code, value))
+            })
+            .collect()
+    }
+
+    // Binary values are written as colon separated hex bytes, unless they are printable.
+    fn hex_value(value: &str) -> String {
+        hex_bytes(value).map_or_else(|| hex(value.as_bytes()), |bytes| hex(&bytes))
+    }
+
+    fn option_words<'a>(block: &'a [Statement], name: &str) -> Option<&'a [String]> {
+        block
+            .iter()
+            .rev()
+            .find(|statement| {
+                statement.key() == Some("option")
+                    && statement.words.get(1).map(String::as_str) == Some(name)
+            })
+            .and_then(|statement| statement.words.get(2..))
+    }
+
+    // Dates are written either as `<weekday> <yyyy/mm/dd> <hh:mm:ss>` in UTC, as `epoch <seconds>`
+    // or as `never`.
+    fn parse_date(words: &[String]) -> Option<u64> {
+        match words {
+            [epoch, seconds, ..] if epoch == "epoch" => seconds.parse().ok(),
+            [_, date, time, ..] => {
+                let mut date = date.split('/').map(str::parse::<i64>);
+                let mut time = time.split(':').map(str::parse::<i64>);
+
+                let (year, month, day) =
+                    (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+                let (hour, minute, second) =
+                    (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+                let seconds =
+                    days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+
+                u64::try_from(seconds).ok()
+            }
+            _ => None,
+        }
+    }
+
+    // Days since 1970-01-01 in the proleptic gregorian calendar.
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+mod grammar {
+    use anyhow::{Result, bail};
+
+    #[derive(Debug)]
+    pub struct Statement {
+        pub words: Vec<String>,
+        pub block: Vec<Statement>,
+    }
+
+    impl Statement {
+        pub fn key(&self) -> Option<&str> {
+            self.words.first().map(String::as_str)
+        }
+    }
+
+    #[derive(Debug)]
+    enum Token {
+        Word(String),
+        Open,
+        Close,
+        End,
+    }
+
+    pub fn parse(content: &str) -> Result<Vec<Statement>> {
+        let mut tokens = tokenize(content)?.into_iter();
+        parse_block(&mut tokens, false)
+    }
+
+    fn parse_block(
+        tokens: &mut impl Iterator<Item = Token>,
+        nested: bool,
+    ) -> Result<Vec<Statement>> {
+        let mut statements = Vec::new();
+        let mut words = Vec::new();
+
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Word(word) => words.push(word),
+                Token::End => {
+                    if !words.is_empty() {
+                        statements.push(Statement {
+                            words: std::mem::take(&mut words),
+                            block: Vec::new(),
+                        });
+                    }
+                }
+                Token::Open => statements.push(Statement {
+                    words: std::mem::take(&mut words),
+                    block: parse_block(tokens, true)?,
+                }),
+                Token::Close if nested && words.is_empty() => return Ok(statements),
+                Token::Close => bail!("Unexpected closing brace."),
+            }
+        }
+
+        if nested || !words.is_empty() {
+            bail!("Unexpected end of file.");
+        }
+
+        Ok(statements)
+    }
+
+    fn tokenize(content: &str) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        let mut chars = content.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => tokens.push(Token::Open),
+                '}' => tokens.push(Token::Close),
+                ';' => tokens.push(Token::End),
+                '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+                '"' => {
+                    let mut word = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => word.extend(chars.next()),
+                            Some(c) => word.push(c),
+                            None => bail!("Unterminated string."),
+                        }
+                    }
+
+                    tokens.push(Token::Word(word));
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    let mut word = String::from(c);
+
+                    while let Some(c) = chars.next_if(|&c| {
+                        !c.is_whitespace() && !matches!(c, '{' | '}' | ';' | '"' | '#')
+                    }) {
+                        word.push(c);
+                    }
+
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::Path};
+
+    use anyhow::{Context as _, Result};
+    use dhcp_template_api::{Interface, Lease4};
+
+    use crate::provider::dhclient::{merge_interfaces, parse_lease_file};
+
+    #[test]
+    fn parses_lease_file() -> Result<()> {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/dhclient/dhclient.eth0.leases");
+        let interfaces = parse_lease_file(&path)?;
+
+        let [interface] = interfaces.as_slice() else {
+            panic!("Expected a single interface, got {interfaces:?}.");
+        };
+
+        assert_eq!(interface.name, "eth0");
+
+        let lease4 = interface.lease4.as_ref().context("Missing lease4.")?;
+        assert_eq!(lease4.dns, ["192.0.2.53", "192.0.2.54"]);
+        assert_eq!(lease4.domain_search, ["example.com", "lab.example.com"]);
+        assert_eq!(lease4.ntp, ["192.0.2.123"]);
+        assert_eq!(
+            lease4.options.get(&6).map(String::as_str),
+            Some("192.0.2.53,192.0.2.54")
+        );
+        assert_eq!(
+            lease4.options.get(&224).map(String::as_str),
+            Some("c0000201")
+        );
+
+        let lease6 = interface.lease6.as_ref().context("Missing lease6.")?;
+        assert_eq!(lease6.dns, ["2001:db8::53", "2001:db8::54"]);
+        assert_eq!(lease6.domain_search, ["example.com"]);
+        assert_eq!(lease6.client_duid.as_deref(), Some("00030001020000000001"));
+        assert_eq!(lease6.prefix6.len(), 1);
+
+        Ok(())
+    }
+    fn interface(address: &str, acquired_at: u64) -> Interface {
+        Interface {
+            name: "eth0".to_owned(),
+            lease4: Some(Lease4 {
+                address: Some(address.to_owned()),
+                acquired_at: Some(acquired_at),
+                expires_at: Some(acquired_at + 3600),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merges_newer_lease() {
+        let mut interfaces = BTreeMap::new();
+        merge_interfaces(&mut interfaces, vec![interface("192.0.2.20", 2000)]);
+        merge_interfaces(&mut interfaces, vec![interface("192.0.2.10", 1000)]);
+
+        let lease4 = interfaces
+            .get("eth0")
+            .and_then(|interface| interface.lease4.as_ref());
+        assert_eq!(
+            lease4.and_then(|lease| lease.address.as_deref()),
+            Some("192.0.2.20")
+        );
+
+        merge_interfaces(&mut interfaces, vec![interface("192.0.2.30", 3000)]);
+
+        let lease4 = interfaces
+            .get("eth0")
+            .and_then(|interface| interface.lease4.as_ref());
+        assert_eq!(
+            lease4.and_then(|lease| lease.address.as_deref()),
+            Some("192.0.2.30")
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     borrow::ToOwned,
     collections::BTreeMap,
+    fmt::Write as _,
     fs::{self, canonicalize, read},
     path::{Path, PathBuf},
 };
@@ -8,12 +9,16 @@ use std::{
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use dhcp_template_api::{Interface, Lease4, Lease6};
+use dhcproto::Name;
 use envconfig::Envconfig;
-use tracing::{Level, instrument};
+use tracing::{Level, debug, instrument};
 
-use crate::provider::notify::{InterfaceReader, NotifyProvider};
+use crate::provider::{
+    lease_time::{acquire4, acquire6, is_expired, modified, now},
+    notify::{InterfaceReader, NotifyProvider},
+};
 
-#[derive(Debug, Envconfig)]
+#[derive(Debug, Clone, Envconfig)]
 pub struct Config {
     #[envconfig(from = "DHCP_TEMPLATE__DHCPCD_PATH", default = "/var/lib/dhcpcd")]
     path: PathBuf,
@@ -44,6 +49,7 @@ impl InterfaceReader for DhcpcdInterfaceReader {
             .map(|entry| entry.path())
             .filter(|path| v4::is_lease_file(path) || v6::is_lease_file(path));
 
+        let now = now()?;
         let mut interfaces = BTreeMap::new();
 
         for lease_file in lease_files {
@@ -51,12 +57,19 @@ impl InterfaceReader for DhcpcdInterfaceReader {
                 anyhow!("Could not extract base name from path {}.", path.display())
             })?;
 
+            // dhcpcd keeps lease files around after the link went down.
+            let lease = parse_lease(&lease_file)?;
+            if is_expired(lease.expires_at(), now) {
+                debug!("Skipping expired lease {}.", lease_file.display());
+                continue;
+            }
+
             let interface = interfaces.entry(name.clone()).or_insert(Interface {
                 name,
                 ..Default::default()
             });
 
-            match parse_lease(&lease_file)? {
+            match lease {
                 Lease::V4(lease4) => interface.lease4 = Some(lease4),
                 Lease::V6(lease6) => interface.lease6 = Some(lease6),
             }
@@ -73,45 +86,324 @@ fn file_base_name(path: &Path) -> Option<String> {
 }
 
 #[derive(Debug)]
-enum Lease {
+pub enum Lease {
     V4(Lease4),
     V6(Lease6),
 }
 
+impl Lease {
+    pub fn expires_at(&self) -> Option<u64> {
+        match self {
+            Self::V4(lease4) => lease4.expires_at,
+            Self::V6(lease6) => lease6.expires_at,
+        }
+    }
+}
+
 #[instrument(ret(level = Level::DEBUG), err(level = Level::WARN))]
 fn parse_lease(path: &Path) -> Result<Lease> {
     let bytes = read(path)?;
+    let acquired_at = modified(path)?;
 
     let lease = if v4::is_lease_file(path) {
-        Lease::V4(v4::decode(&bytes)?)
+        let mut lease4 = v4::decode(&bytes)?;
+        acquire4(&mut lease4, acquired_at);
+        Lease::V4(lease4)
     } else {
-        Lease::V6(v6::decode(&bytes)?)
+        let mut lease6 = v6::decode(&bytes)?;
+        acquire6(&mut lease6, acquired_at);
+        Lease::V6(lease6)
     };
 
     Ok(lease)
 }
 
-mod v4 {
-    use std::{path::Path, string::ToString};
+// Options without a known type are passed as lowercase hex bytes.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+// Decoded names are fully qualified, but the trailing dot is not expected by templates.
+fn domain(name: &Name) -> String {
+    name.to_string().trim_end_matches('.').to_owned()
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub mod v4 {
+    use std::{collections::BTreeMap, net::Ipv4Addr, path::Path, string::ToString};
 
     use anyhow::{Context as _, Result};
-    use dhcp_template_api::Lease4;
+    use dhcp_template_api::{Lease4, Route};
     use dhcproto::{
-        Decodable as _, Decoder,
+        Decodable as _, Decoder, Encodable as _,
         v4::{DhcpOption, DhcpOptions, Message, OptionCode},
     };
 
+    const MS_CLASSLESS_STATIC_ROUTE: u8 = 249;
+
     pub fn decode(bytes: &[u8]) -> Result<Lease4> {
         let mut decoder = Decoder::new(bytes);
         let message = Message::decode(&mut decoder).context("Could not decode dhcpv4 message.")?;
+
+        Ok(from_message(&message))
+    }
+
+    pub fn from_message(message: &Message) -> Lease4 {
         let options = message.opts();
 
-        let lease = Lease4 {
+        Lease4 {
             dns: get_dns(options),
             domain: get_domain(options),
+            address: get_address(message),
+            prefix_len: get_prefix_len(options),
+            routers: get_routers(options),
+            server_id: get_server_id(options),
+            lease_time_seconds: get_seconds(options, OptionCode::AddressLeaseTime),
+            renewal_time_seconds: get_seconds(options, OptionCode::Renewal),
+            rebinding_time_seconds: get_seconds(options, OptionCode::Rebinding),
+            options: get_options(options),
+            domain_search: get_domain_search(options),
+            ntp: get_ntp(options),
+            mtu: get_mtu(options),
+            routes: get_routes(options),
+            // Messages do not know when they were received, see `acquire4`.
+            ..Default::default()
+        }
+    }
+
+    fn get_routes(options: &DhcpOptions) -> Vec<Route> {
+        match options.get(OptionCode::ClasslessStaticRoute) {
+            Some(DhcpOption::ClasslessStaticRoute(routes)) => routes
+                .iter()
+                .map(|(destination, gateway)| Route {
+                    destination: destination.network().to_string(),
+                    prefix_len: u32::from(destination.prefix_len()),
+                    gateway: (!gateway.is_unspecified()).then(|| gateway.to_string()),
+                })
+                .collect(),
+            _ => match options.get(OptionCode::Unknown(MS_CLASSLESS_STATIC_ROUTE)) {
+                Some(DhcpOption::Unknown(unknown)) => {
+                    parse_classless_routes(unknown.data()).unwrap_or_default()
+                }
+                _ => Vec::default(),
+            },
+        }
+    }
+
+    // Each route is encoded as the prefix length, the significant octets of the destination and
+    // the gateway, where a gateway of 0.0.0.0 is on-link.
+    pub fn parse_classless_routes(mut bytes: &[u8]) -> Option<Vec<Route>> {
+        let mut routes = Vec::new();
+
+        while let Some((&prefix_len, rest)) = bytes.split_first() {
+            let significant = usize::from(prefix_len.div_ceil(8));
+            let destination = rest.get(..significant)?;
+            let gateway: [u8; 4] = rest.get(significant..significant + 4)?.try_into().ok()?;
+
+            let mut octets = [0; 4];
+            octets.get_mut(..significant)?.copy_from_slice(destination);
+
+            let gateway = Ipv4Addr::from(gateway);
+
+            routes.push(Route {
+                destination: Ipv4Addr::from(octets).to_string(),
+                prefix_len: u32::from(prefix_len),
+                gateway: (!gateway.is_unspecified()).then(|| gateway.to_string()),
+            });
+
+            bytes = rest.get(significant + 4..)?;
+        }
+
+        Some(routes)
+    }
+
+    fn get_domain_search(options: &DhcpOptions) -> Vec<String> {
+        if let Some(DhcpOption::DomainSearch(names)) = options.get(OptionCode::DomainSearch) {
+            names.iter().map(super::domain).collect()
+        } else {
+            Vec::default()
+        }
+    }
+
+    fn get_ntp(options: &DhcpOptions) -> Vec<String> {
+        if let Some(DhcpOption::NtpServers(ntp)) = options.get(OptionCode::NtpServers) {
+            ntp.iter().map(ToString::to_string).collect()
+        } else {
+            Vec::default()
+        }
+    }
+
+    fn get_mtu(options: &DhcpOptions) -> Option<u32> {
+        if let Some(DhcpOption::InterfaceMtu(mtu)) = options.get(OptionCode::InterfaceMtu) {
+            Some(u32::from(*mtu))
+        } else {
+            None
+        }
+    }
+
+    fn get_options(options: &DhcpOptions) -> BTreeMap<u32, String> {
+        options
+            .iter()
+            .filter_map(|(code, option)| Some((u32::from(u8::from(*code)), render(option)?)))
+            .collect()
+    }
+
+    fn render(option: &DhcpOption) -> Option<String> {
+        use DhcpOption as O;
+
+        let value = match option {
+            O::Pad | O::End => return None,
+            O::SubnetMask(ip)
+            | O::SwapServer(ip)
+            | O::BroadcastAddr(ip)
+            | O::RouterSolicitationAddr(ip)
+            | O::RequestedIpAddress(ip)
+            | O::ServerIdentifier(ip)
+            | O::SubnetSelection(ip)
+            | O::TFTPServerAddress(ip) => ip.to_string(),
+            O::TimeServer(ips)
+            | O::NameServer(ips)
+            | O::Router(ips)
+            | O::DomainNameServer(ips)
+            | O::LogServer(ips)
+            | O::QuoteServer(ips)
+            | O::LprServer(ips)
+            | O::ImpressServer(ips)
+            | O::ResourceLocationServer(ips)
+            | O::XFontServer(ips)
+            | O::XDisplayManager(ips)
+            | O::NisServers(ips)
+            | O::NtpServers(ips)
+            | O::NetBiosNameServers(ips)
+            | O::NetBiosDatagramDistributionServer(ips)
+            | O::AssociatedIp(ips)
+            | O::NispServers(ips)
+            | O::MobileIpHomeAgent(ips)
+            | O::Pop3Server(ips)
+            | O::NntpServer(ips)
+            | O::WwwServer(ips)
+            | O::DefaultFingerServer(ips)
+            | O::StreetTalkServer(ips)
+            | O::StreetTalkDirectoryAssistance(ips)
+            | O::SmtpServer(ips)
+            | O::IrcServer(ips)
+            | O::BcmsControllerAddrs(ips) => super::join(ips),
+            O::Hostname(s)
+            | O::MeritDumpFile(s)
+            | O::DomainName(s)
+            | O::ExtensionsPath(s)
+            | O::NisDomain(s)
+            | O::RootPath(s)
+            | O::NetBiosScope(s)
+            | O::Message(s)
+            | O::NwipDomainName(s)
+            | O::NispServiceDomain(s)
+            | O::TimezonePosixString(s)
+            | O::TimezoneDatabaseString(s)
+            | O::CaptivePortal(s) => s.clone(),
+            O::BootFileSize(num)
+            | O::MaxDatagramSize(num)
+            | O::InterfaceMtu(num)
+            | O::MaxMessageSize(num) => num.to_string(),
+            O::ArpCacheTimeout(num)
+            | O::TcpKeepaliveInterval(num)
+            | O::AddressLeaseTime(num)
+            | O::Renewal(num)
+            | O::Rebinding(num)
+            | O::PathMtuAgingTimeout(num)
+            | O::Ipv6OnlyPreferred(num) => num.to_string(),
+            O::DefaultIpTtl(num) | O::DefaultTcpTtl(num) => num.to_string(),
+            O::TimeOffset(offset) => offset.to_string(),
+            O::IpForwarding(b)
+            | O::NonLocalSrcRouting(b)
+            | O::AllSubnetsLocal(b)
+            | O::PerformMaskDiscovery(b)
+            | O::MaskSupplier(b)
+            | O::PerformRouterDiscovery(b)
+            | O::EthernetEncapsulation(b)
+            | O::TcpKeepaliveGarbage(b)
+            | O::TrailerEncapsulated(b) => b.to_string(),
+            O::MessageType(message_type) => u8::from(*message_type).to_string(),
+            O::DomainSearch(names) | O::BcmsControllerNames(names) => {
+                super::join(&names.iter().map(super::domain).collect::<Vec<_>>())
+            }
+            O::ClasslessStaticRoute(routes) => super::join(
+                &routes
+                    .iter()
+                    .map(|(destination, gateway)| format!("{destination} {gateway}"))
+                    .collect::<Vec<_>>(),
+            ),
+            O::Unknown(unknown) => super::hex(unknown.data()),
+            _ => super::hex(&payload(&option.to_vec().ok()?)?),
         };
 
-        Ok(lease)
+        Some(value)
+    }
+
+    // Strips the code and length of the encoded option. Options longer than 255 bytes are encoded as
+    // several chunks, each with its own code and length.
+    fn payload(mut encoded: &[u8]) -> Option<Vec<u8>> {
+        let mut payload = Vec::new();
+
+        while let [_, len, rest @ ..] = encoded {
+            let (chunk, rest) = rest.split_at_checked(usize::from(*len))?;
+            payload.extend_from_slice(chunk);
+            encoded = rest;
+        }
+
+        encoded.is_empty().then_some(payload)
+    }
+
+    fn get_address(message: &Message) -> Option<String> {
+        let address = message.yiaddr();
+        (!address.is_unspecified()).then(|| address.to_string())
+    }
+
+    fn get_prefix_len(options: &DhcpOptions) -> Option<u32> {
+        if let Some(DhcpOption::SubnetMask(mask)) = options.get(OptionCode::SubnetMask) {
+            Some(u32::from(*mask).leading_ones())
+        } else {
+            None
+        }
+    }
+
+    fn get_routers(options: &DhcpOptions) -> Vec<String> {
+        if let Some(DhcpOption::Router(routers)) = options.get(OptionCode::Router) {
+            routers.iter().map(ToString::to_string).collect()
+        } else {
+            Vec::default()
+        }
+    }
+
+    fn get_server_id(options: &DhcpOptions) -> Option<String> {
+        if let Some(DhcpOption::ServerIdentifier(server_id)) =
+            options.get(OptionCode::ServerIdentifier)
+        {
+            Some(server_id.to_string())
+        } else {
+            None
+        }
+    }
+
+    fn get_seconds(options: &DhcpOptions, code: OptionCode) -> Option<u32> {
+        match options.get(code) {
+            Some(
+                DhcpOption::AddressLeaseTime(seconds)
+                | DhcpOption::Renewal(seconds)
+                | DhcpOption::Rebinding(seconds),
+            ) => Some(*seconds),
+            _ => None,
+        }
     }
 
     fn get_dns(options: &DhcpOptions) -> Vec<String> {
@@ -136,27 +428,120 @@ mod v4 {
     }
 }
 
-mod v6 {
-    use std::{path::Path, string::ToString};
+pub mod v6 {
+    use std::{collections::BTreeMap, path::Path, string::ToString};
 
     use anyhow::{Context as _, Result};
-    use dhcp_template_api::{Lease6, Prefix6};
+    use dhcp_template_api::{Address6, Lease6, Prefix6};
     use dhcproto::{
-        Decodable as _, Decoder,
-        v6::{DhcpOption, DhcpOptions, Message, OptionCode},
+        Decodable as _, Decoder, Encodable as _,
+        v6::{DhcpOption, DhcpOptions, Message, NtpSuboption, OptionCode},
     };
 
     pub fn decode(bytes: &[u8]) -> Result<Lease6> {
         let mut decoder = Decoder::new(bytes);
         let message = Message::decode(&mut decoder).context("Could not decode dhcpv6 message.")?;
+
+        Ok(from_message(&message))
+    }
+
+    pub fn from_message(message: &Message) -> Lease6 {
         let options = message.opts();
 
-        let lease = Lease6 {
+        Lease6 {
             dns: get_dns(options),
             prefix6: get_prefix(options),
+            address6: get_address(options),
+            options: get_options(options),
+            domain_search: get_domain_search(options),
+            ntp: get_ntp(options),
+            server_duid: get_duid(options, OptionCode::ServerId),
+            client_duid: get_duid(options, OptionCode::ClientId),
+            // Messages do not know when they were received, see `acquire6`.
+            ..Default::default()
+        }
+    }
+
+    fn get_domain_search(options: &DhcpOptions) -> Vec<String> {
+        options
+            .get_all(OptionCode::DomainSearchList)
+            .into_iter()
+            .flatten()
+            .filter_map(|option| {
+                if let DhcpOption::DomainSearchList(names) = option {
+                    Some(names)
+                } else {
+                    None
+                }
+            })
+            .flat_map(|names| names.iter().map(super::domain))
+            .collect()
+    }
+
+    fn get_ntp(options: &DhcpOptions) -> Vec<String> {
+        options
+            .get_all(OptionCode::NtpServer)
+            .into_iter()
+            .flatten()
+            .filter_map(|option| {
+                if let DhcpOption::NtpServer(suboptions) = option {
+                    Some(suboptions)
+                } else {
+                    None
+                }
+            })
+            .flat_map(|suboptions| suboptions.iter().map(ntp_server))
+            .collect()
+    }
+
+    fn ntp_server(suboption: &NtpSuboption) -> String {
+        match suboption {
+            NtpSuboption::ServerAddress(ip) | NtpSuboption::MulticastAddress(ip) => ip.to_string(),
+            NtpSuboption::FQDN(name) => super::domain(name),
+        }
+    }
+
+    fn get_duid(options: &DhcpOptions, code: OptionCode) -> Option<String> {
+        match options.get(code)? {
+            DhcpOption::ServerId(duid) | DhcpOption::ClientId(duid) => Some(super::hex(duid)),
+            _ => None,
+        }
+    }
+
+    fn get_options(options: &DhcpOptions) -> BTreeMap<u32, String> {
+        options
+            .iter()
+            .filter_map(|option| {
+                Some((
+                    u32::from(u16::from(OptionCode::from(option))),
+                    render(option)?,
+                ))
+            })
+            .collect()
+    }
+
+    fn render(option: &DhcpOption) -> Option<String> {
+        use DhcpOption as O;
+
+        let value = match option {
+            O::ClientId(bytes) | O::ServerId(bytes) | O::InterfaceId(bytes) => super::hex(bytes),
+            O::Preference(num) => num.to_string(),
+            O::ElapsedTime(num) => num.to_string(),
+            O::InformationRefreshTime(num) => num.to_string(),
+            O::ServerUnicast(ip) => ip.to_string(),
+            O::DomainNameServers(ips) => super::join(ips),
+            O::DomainSearchList(names) => {
+                super::join(&names.iter().map(super::domain).collect::<Vec<_>>())
+            }
+            O::NtpServer(suboptions) => {
+                super::join(&suboptions.iter().map(ntp_server).collect::<Vec<_>>())
+            }
+            O::Unknown(unknown) => super::hex(unknown.data()),
+            // Strips the code and length of the encoded option.
+            _ => super::hex(option.to_vec().ok()?.get(4..)?),
         };
 
-        Ok(lease)
+        Some(value)
     }
 
     fn get_dns(options: &DhcpOptions) -> Vec<String> {
@@ -187,18 +572,61 @@ mod v6 {
                     None
                 }
             })
-            .filter_map(|iapd| iapd.opts.get_all(OptionCode::IAPrefix))
+            .flat_map(|iapd| {
+                iapd.opts
+                    .get_all(OptionCode::IAPrefix)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(move |option| {
+                        if let DhcpOption::IAPrefix(iaprefix) = option {
+                            Some(Prefix6 {
+                                ip: iaprefix.prefix_ip.to_string(),
+                                len: u32::from(iaprefix.prefix_len),
+                                preferred_lifetime_seconds: Some(iaprefix.preferred_lifetime),
+                                valid_lifetime_seconds: Some(iaprefix.valid_lifetime),
+                                renewal_time_seconds: Some(iapd.t1),
+                                rebinding_time_seconds: Some(iapd.t2),
+                                iaid: Some(iapd.id),
+                            })
+                        } else {
+                            None
+                        }
+                    })
+            })
+            .collect()
+    }
+
+    fn get_address(options: &DhcpOptions) -> Vec<Address6> {
+        options
+            .get_all(OptionCode::IANA)
+            .into_iter()
             .flatten()
             .filter_map(|option| {
-                if let DhcpOption::IAPrefix(iaprefix) = option {
-                    Some(iaprefix)
+                if let DhcpOption::IANA(iana) = option {
+                    Some(iana)
                 } else {
                     None
                 }
             })
-            .map(|iaprefix| Prefix6 {
-                ip: iaprefix.prefix_ip.to_string(),
-                len: u32::from(iaprefix.prefix_len),
+            .flat_map(|iana| {
+                iana.opts
+                    .get_all(OptionCode::IAAddr)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(move |option| {
+                        if let DhcpOption::IAAddr(iaaddr) = option {
+                            Some(Address6 {
+                                ip: iaaddr.addr.to_string(),
+                                preferred_lifetime_seconds: Some(iaaddr.preferred_life),
+                                valid_lifetime_seconds: Some(iaaddr.valid_life),
+                                renewal_time_seconds: Some(iana.t1),
+                                rebinding_time_seconds: Some(iana.t2),
+                                iaid: Some(iana.id),
+                            })
+                        } else {
+                            None
+                        }
+                    })
             })
             .collect()
     }
@@ -208,3 +636,96 @@ mod v6 {
             .is_some_and(|ext| matches!(ext.to_str(), Some("lease6")))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use dhcp_template_api::Route;
+
+    use crate::provider::dhcpcd::{v4, v4::parse_classless_routes, v6};
+
+    fn route(destination: &str, prefix_len: u32, gateway: Option<&str>) -> Route {
+        Route {
+            destination: destination.to_owned(),
+            prefix_len,
+            gateway: gateway.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn decodes_v4_lease() -> Result<()> {
+        let lease4 = v4::decode(include_bytes!("../../fixtures/dhcpcd/eth0.lease"))?;
+
+        assert_eq!(lease4.address.as_deref(), Some("192.0.2.10"));
+        assert_eq!(lease4.dns, ["192.0.2.53", "192.0.2.54"]);
+        assert_eq!(lease4.domain.as_deref(), Some("example.com"));
+        assert_eq!(lease4.domain_search, ["example.com", "lab.example.com"]);
+        assert_eq!(lease4.ntp, ["192.0.2.123"]);
+        assert_eq!(
+            lease4.options.get(&6).map(String::as_str),
+            Some("192.0.2.53,192.0.2.54")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_v6_reply() -> Result<()> {
+        let lease6 = v6::decode(include_bytes!("../../fixtures/dhcpcd/eth0.lease6"))?;
+
+        assert_eq!(lease6.dns, ["2001:db8::53", "2001:db8::54"]);
+        assert_eq!(lease6.domain_search, ["example.com"]);
+        assert_eq!(lease6.ntp, ["2001:db8::123", "ntp.example.com"]);
+        assert_eq!(lease6.prefix6.len(), 1);
+        assert_eq!(lease6.prefix6[0].ip, "2001:db8:1::");
+        assert_eq!(lease6.prefix6[0].len, 56);
+        assert_eq!(lease6.prefix6[0].preferred_lifetime_seconds, Some(3600));
+        assert_eq!(lease6.prefix6[0].valid_lifetime_seconds, Some(7200));
+        assert_eq!(lease6.prefix6[0].renewal_time_seconds, Some(1800));
+        assert_eq!(lease6.prefix6[0].rebinding_time_seconds, Some(2880));
+        assert_eq!(lease6.prefix6[0].iaid, Some(1));
+        assert_eq!(lease6.client_duid.as_deref(), Some("00030001020000000001"));
+        assert_eq!(lease6.server_duid.as_deref(), Some("00030001020000000002"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_default_route() {
+        assert_eq!(
+            parse_classless_routes(&[0, 192, 0, 2, 1]),
+            Some(vec![route("0.0.0.0", 0, Some("192.0.2.1"))])
+        );
+    }
+
+    #[test]
+    fn parses_non_octet_prefix_lengths() {
+        assert_eq!(
+            parse_classless_routes(&[12, 172, 16, 192, 0, 2, 1, 25, 10, 0, 1, 128, 0, 0, 0, 0]),
+            Some(vec![
+                route("172.16.0.0", 12, Some("192.0.2.1")),
+                route("10.0.1.128", 25, None),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_empty_routes() {
+        assert_eq!(parse_classless_routes(&[]), Some(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_truncated_routes() {
+        assert_eq!(parse_classless_routes(&[24, 10, 0]), None);
+        assert_eq!(parse_classless_routes(&[24, 10, 0, 1, 192, 0, 2]), None);
+        assert_eq!(parse_classless_routes(&[0, 192, 0, 2, 1, 8]), None);
+    }
+
+    #[test]
+    fn rejects_invalid_prefix_lengths() {
+        assert_eq!(
+            parse_classless_routes(&[33, 10, 0, 0, 0, 0, 192, 0, 2, 1]),
+            None
+        );
+    }
+}
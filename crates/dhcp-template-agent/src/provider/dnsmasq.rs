@@ -0,0 +1,91 @@
+use std::{
+    fs::{self, canonicalize, read_to_string},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use dhcp_template_api::ServedLease;
+use envconfig::Envconfig;
+use tracing::{Level, instrument};
+
+use crate::provider::notify::{InterfaceReader, NotifyProvider};
+
+const HOSTNAME_UNKNOWN: &str = "*";
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(from = "DHCP_TEMPLATE__DNSMASQ_PATH", default = "/var/lib/misc")]
+    path: PathBuf,
+}
+
+pub type DnsmasqProvider = NotifyProvider<DnsmasqInterfaceReader>;
+
+impl TryFrom<Config> for DnsmasqProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let path = canonicalize(config.path)?;
+        let provider = Self::new(path, DnsmasqInterfaceReader);
+
+        Ok(provider)
+    }
+}
+
+#[derive(Debug)]
+pub struct DnsmasqInterfaceReader;
+
+#[async_trait]
+impl InterfaceReader for DnsmasqInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn served(&self, path: &Path) -> Result<Vec<ServedLease>> {
+        let lease_files = fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_lease_file(path));
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut served = Vec::new();
+
+        for lease_file in lease_files {
+            served.extend(
+                parse_leases(&lease_file)?
+                    .into_iter()
+                    .filter(|lease| lease.expires.is_none_or(|expires| expires > now)),
+            );
+        }
+
+        Ok(served)
+    }
+}
+
+fn is_lease_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| name.starts_with("dnsmasq") && name.ends_with(".leases"))
+}
+
+// Each line is `<expiry> <mac or iaid> <address> <hostname> <client id>`. DHCPv6 leases follow a
+// `duid <server duid>` line and carry the iaid instead of a mac. An expiry of zero never expires.
+#[instrument(ret(level = Level::DEBUG), err(level = Level::WARN))]
+fn parse_leases(path: &Path) -> Result<Vec<ServedLease>> {
+    let content = read_to_string(path)?;
+
+    let leases = content
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter_map(|fields| match fields.as_slice() {
+            ["duid", ..] => None,
+            [expires, mac, address, hostname, ..] => Some(ServedLease {
+                address: (*address).to_owned(),
+                mac: (!address.contains(':')).then(|| (*mac).to_owned()),
+                hostname: (*hostname != HOSTNAME_UNKNOWN).then(|| (*hostname).to_owned()),
+                expires: expires.parse().ok().filter(|&expires| expires != 0),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Ok(leases)
+}
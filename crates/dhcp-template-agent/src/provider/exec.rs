@@ -0,0 +1,121 @@
+use std::{path::PathBuf, process::Stdio, time::Duration};
+
+use anyhow::{Context as _, Result, bail};
+use dhcp_template_api::{Interface, Node};
+use envconfig::Envconfig;
+use futures_time::stream::interval;
+use futures_util::{
+    FutureExt as _, StreamExt as _, TryStreamExt as _,
+    future::{pending, ready},
+    stream::{BoxStream, once, select_all},
+};
+use notify::RecursiveMode;
+use tokio::{process::Command, time::timeout};
+use tracing::{Level, info, instrument};
+
+use crate::provider::{Provider, notify::changes};
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(from = "DHCP_TEMPLATE__EXEC_COMMAND")]
+    command: Option<String>,
+
+    #[envconfig(from = "DHCP_TEMPLATE__EXEC_INTERVAL_SECONDS")]
+    interval_seconds: Option<u64>,
+
+    #[envconfig(from = "DHCP_TEMPLATE__EXEC_WATCH_PATH")]
+    watch_path: Option<PathBuf>,
+
+    #[envconfig(from = "DHCP_TEMPLATE__EXEC_TIMEOUT_SECONDS", default = "10")]
+    timeout_seconds: u64,
+}
+
+// Runs a shell command whose stdout is a json list of interfaces. The command runs once on start,
+// and then on every interval and change of the watched path, if any. Failed runs are skipped, so the
+// operator keeps the last known state.
+#[derive(Debug)]
+pub struct ExecProvider {
+    command: String,
+    interval: Option<Duration>,
+    watch_path: Option<PathBuf>,
+    timeout: Duration,
+}
+
+impl TryFrom<Config> for ExecProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let Some(command) = config.command else {
+            bail!("DHCP_TEMPLATE__EXEC_COMMAND is required for the exec provider.");
+        };
+
+        let provider = Self {
+            command,
+            interval: config.interval_seconds.map(Duration::from_secs),
+            watch_path: config.watch_path,
+            timeout: Duration::from_secs(config.timeout_seconds),
+        };
+
+        Ok(provider)
+    }
+}
+
+impl Provider for ExecProvider {
+    fn node(&self) -> BoxStream<'_, Result<Node>> {
+        // The agent treats a finished provider as an error, so keep the stream open when the
+        // command only runs on start.
+        let mut triggers = vec![
+            once(ready(Ok(()))).boxed(),
+            once(pending::<Result<()>>()).boxed(),
+        ];
+
+        if let Some(period) = self.interval {
+            triggers.push(interval(period.into()).map(|_| Ok(())).boxed());
+        }
+
+        if let Some(path) = &self.watch_path {
+            triggers.push(
+                changes(path, RecursiveMode::NonRecursive)
+                    .map_ok(|_| ())
+                    .boxed(),
+            );
+        }
+
+        select_all(triggers)
+            .try_filter_map(async |()| Ok(self.execute().await.ok()))
+            .map_ok(|interfaces| Node {
+                interfaces,
+                ..Default::default()
+            })
+            .boxed()
+    }
+}
+
+impl ExecProvider {
+    #[instrument(skip(self), fields(command = self.command), err(level = Level::WARN))]
+    async fn execute(&self) -> Result<Vec<Interface>> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+
+        let output = timeout(self.timeout, output)
+            .map(|result| result.context("Command timed out."))
+            .await??;
+
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            info!("{line}");
+        }
+
+        if !output.status.success() {
+            bail!("Command failed with {}.", output.status);
+        }
+
+        let interfaces =
+            serde_json::from_slice(&output.stdout).context("Could not parse command output.")?;
+
+        Ok(interfaces)
+    }
+}
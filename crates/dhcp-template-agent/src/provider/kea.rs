@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, canonicalize, read_to_string},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use dhcp_template_api::ServedLease;
+use envconfig::Envconfig;
+use tracing::{Level, instrument};
+
+use crate::provider::notify::{InterfaceReader, NotifyProvider};
+
+// Memfile lease files are appended to and periodically compacted, which moves the current file to
+// `.2` and merges it into `.1`. Read in this order, later rows supersede earlier ones.
+const COMPACTION_SUFFIXES: [&str; 3] = [".1", ".2", ""];
+
+const STATE_DEFAULT: &str = "0";
+const LEASE_TYPE_PREFIX: &str = "2";
+const LIFETIME_INFINITE: &str = "4294967295";
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(from = "DHCP_TEMPLATE__KEA_PATH", default = "/var/lib/kea")]
+    path: PathBuf,
+}
+
+pub type KeaProvider = NotifyProvider<KeaInterfaceReader>;
+
+impl TryFrom<Config> for KeaProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let path = canonicalize(config.path)?;
+        let provider = Self::new(path, KeaInterfaceReader);
+
+        Ok(provider)
+    }
+}
+
+#[derive(Debug)]
+pub struct KeaInterfaceReader;
+
+#[async_trait]
+impl InterfaceReader for KeaInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn served(&self, path: &Path) -> Result<Vec<ServedLease>> {
+        let lease_files = fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_lease_file(path));
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut served = Vec::new();
+
+        for lease_file in lease_files {
+            let mut leases = BTreeMap::new();
+
+            for suffix in COMPACTION_SUFFIXES {
+                let mut path = lease_file.clone().into_os_string();
+                path.push(suffix);
+
+                let path = PathBuf::from(path);
+                if path.is_file() {
+                    parse_leases(&path, &mut leases)?;
+                }
+            }
+
+            served.extend(
+                leases
+                    .into_values()
+                    .flatten()
+                    .filter(|lease| lease.expires.is_none_or(|expires| expires > now)),
+            );
+        }
+
+        Ok(served)
+    }
+}
+
+fn is_lease_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| matches!(ext.to_str(), Some("csv")))
+}
+
+// Leases are keyed by address, so released or reclaimed leases (`None`) remove earlier rows.
+#[instrument(skip(leases), err(level = Level::WARN))]
+fn parse_leases(path: &Path, leases: &mut BTreeMap<String, Option<ServedLease>>) -> Result<()> {
+    let content = read_to_string(path)?;
+    let mut lines = content.lines();
+
+    let header: HashMap<&str, usize> = lines
+        .next()
+        .with_context(|| format!("Missing csv header in {}.", path.display()))?
+        .split(',')
+        .enumerate()
+        .map(|(index, column)| (column.trim(), index))
+        .collect();
+
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let row: Vec<&str> = line.split(',').collect();
+        let column = |name: &str| {
+            header
+                .get(name)
+                .and_then(|&index| row.get(index))
+                .map(|value| value.replace("&#x2c", ","))
+                .filter(|value| !value.is_empty())
+        };
+
+        let Some(address) = column("address") else {
+            continue;
+        };
+
+        // Delegated prefixes are not leases of a single client.
+        if column("lease_type").as_deref() == Some(LEASE_TYPE_PREFIX) {
+            continue;
+        }
+
+        let valid_lifetime = column("valid_lifetime");
+        let active = column("state").is_none_or(|state| state == STATE_DEFAULT)
+            && valid_lifetime.as_deref() != Some("0");
+
+        let lease = active.then(|| ServedLease {
+            address: address.clone(),
+            mac: column("hwaddr"),
+            hostname: column("hostname"),
+            expires: column("expire")
+                .filter(|_| valid_lifetime.as_deref() != Some(LIFETIME_INFINITE))
+                .and_then(|expire| expire.parse().ok()),
+        });
+
+        leases.insert(address, lease);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::{
+    fs::metadata,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use dhcp_template_api::{Lease4, Lease6, Node};
+
+pub const LIFETIME_INFINITE: u32 = u32::MAX;
+
+pub fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+// Clients rewrite their lease file whenever a lease is acquired or renewed.
+pub fn modified(path: &Path) -> Result<u64> {
+    Ok(metadata(path)?
+        .modified()?
+        .duration_since(UNIX_EPOCH)?
+        .as_secs())
+}
+
+pub fn acquire4(lease: &mut Lease4, acquired_at: u64) {
+    lease.acquired_at = Some(acquired_at);
+    lease.expires_at = lease
+        .lease_time_seconds
+        .and_then(|lifetime| expires(acquired_at, lifetime));
+}
+
+pub fn acquire6(lease: &mut Lease6, acquired_at: u64) {
+    let lifetimes = lease
+        .prefix6
+        .iter()
+        .map(|prefix| prefix.valid_lifetime_seconds)
+        .chain(
+            lease
+                .address6
+                .iter()
+                .map(|address| address.valid_lifetime_seconds),
+        )
+        .collect::<Option<Vec<_>>>();
+
+    lease.acquired_at = Some(acquired_at);
+    lease.expires_at = lifetimes
+        .and_then(|lifetimes| lifetimes.into_iter().max())
+        .and_then(|lifetime| expires(acquired_at, lifetime));
+}
+
+pub fn is_expired(expires_at: Option<u64>, now: u64) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at <= now)
+}
+
+// The earliest lease of the node that has not expired yet, whether held or served.
+pub fn next_expiry(node: &Node, now: u64) -> Option<u64> {
+    node.interfaces
+        .iter()
+        .flat_map(|interface| {
+            [
+                interface.lease4.as_ref().and_then(|lease| lease.expires_at),
+                interface.lease6.as_ref().and_then(|lease| lease.expires_at),
+            ]
+        })
+        .chain(node.served.iter().map(|lease| lease.expires))
+        .flatten()
+        .filter(|&expires_at| expires_at > now)
+        .min()
+}
+
+fn expires(acquired_at: u64, lifetime: u32) -> Option<u64> {
+    (lifetime != LIFETIME_INFINITE).then(|| acquired_at.saturating_add(u64::from(lifetime)))
+}
@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use dhcp_template_api::Node;
+use envconfig::Envconfig;
+use futures_util::{
+    StreamExt as _, TryStreamExt as _,
+    future::ready,
+    stream::{BoxStream, select},
+};
+use tracing::debug;
+
+use crate::provider::{Provider, netlink};
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(from = "DHCP_TEMPLATE__LINK_ENABLED", default = "false")]
+    pub enabled: bool,
+
+    #[envconfig(from = "DHCP_TEMPLATE__LINK_SYSFS_PATH", default = "/sys/class/net")]
+    sysfs_path: PathBuf,
+}
+
+// Adds link metadata to the interfaces of another provider, matched by name. Metadata is read from
+// sysfs and addresses over netlink, which both reflect the network namespace of the agent unless
+// the host sysfs is mounted at `DHCP_TEMPLATE__LINK_SYSFS_PATH`. Fields reported by the provider
+// are kept. The latest node of the provider is enriched again whenever links change.
+#[derive(Debug)]
+pub struct LinkProvider {
+    provider: Box<dyn Provider>,
+    sysfs_path: PathBuf,
+}
+
+impl LinkProvider {
+    pub fn new(provider: Box<dyn Provider>, config: Config) -> Self {
+        Self {
+            provider,
+            sysfs_path: config.sysfs_path,
+        }
+    }
+
+    async fn enrich(&self, mut node: Node) -> Node {
+        for interface in &mut node.interfaces {
+            let link = self.sysfs_path.join(&interface.name);
+
+            interface.mac = interface.mac.take().or_else(|| read(&link, "address"));
+            interface.mtu = interface
+                .mtu
+                .or_else(|| read(&link, "mtu").and_then(|mtu| mtu.parse().ok()));
+            interface.operstate = interface
+                .operstate
+                .take()
+                .or_else(|| read(&link, "operstate"));
+            interface.ifindex = interface
+                .ifindex
+                .or_else(|| read(&link, "ifindex").and_then(|index| index.parse().ok()));
+        }
+
+        if node
+            .interfaces
+            .iter()
+            .any(|interface| interface.addresses.is_empty())
+        {
+            let links: HashMap<_, _> = netlink::interfaces()
+                .await
+                .inspect_err(|err| debug!("Skipping link addresses: {err:#}"))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|link| (link.name, link.addresses))
+                .collect();
+
+            for interface in node
+                .interfaces
+                .iter_mut()
+                .filter(|interface| interface.addresses.is_empty())
+            {
+                if let Some(addresses) = links.get(&interface.name) {
+                    interface.addresses.clone_from(addresses);
+                }
+            }
+        }
+
+        node
+    }
+}
+
+impl Provider for LinkProvider {
+    fn node(&self) -> BoxStream<'_, Result<Node>> {
+        let nodes = self.provider.node().map_ok(Some);
+        let links = netlink::link_changes().map_ok(|()| None);
+        let mut latest = None;
+
+        select(nodes, links)
+            .try_filter_map(move |node| {
+                if node.is_some() {
+                    latest.clone_from(&node);
+                }
+
+                ready(Ok(latest.clone()))
+            })
+            .and_then(async |node| Ok(self.enrich(node).await))
+            .boxed()
+    }
+}
+
+fn read(link: &Path, attribute: &str) -> Option<String> {
+    read_to_string(link.join(attribute))
+        .ok()
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
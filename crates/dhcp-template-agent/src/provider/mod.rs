@@ -1,35 +1,116 @@
+mod composite;
+mod dhclient;
 mod dhcpcd;
+mod dnsmasq;
+mod exec;
+mod kea;
+mod lease_time;
+mod link;
+mod netlink;
+mod network_manager;
+mod networkd;
 mod notify;
+mod odhcp6c;
+mod pcap;
+mod sd_lease;
+mod static_file;
+
+use std::str::FromStr;
 
 use anyhow::Result;
-use dhcp_template_api::Interface;
+use dhcp_template_api::Node;
 use envconfig::Envconfig;
 use futures_util::stream::BoxStream;
 use strum::{Display, EnumString};
 use tracing::{Level, instrument};
 
-use crate::provider::dhcpcd::DhcpcdProvider;
+use crate::provider::{
+    composite::CompositeProvider, dhclient::DhclientProvider, dhcpcd::DhcpcdProvider,
+    dnsmasq::DnsmasqProvider, exec::ExecProvider, kea::KeaProvider, link::LinkProvider,
+    netlink::NetlinkProvider, network_manager::NetworkManagerProvider, networkd::NetworkdProvider,
+    odhcp6c::Odhcp6cProvider, pcap::PcapProvider, static_file::StaticProvider,
+};
 
 #[derive(Debug, Display, EnumString)]
 #[strum(ascii_case_insensitive, serialize_all = "snake_case")]
 enum Implementation {
     Dhcpcd,
+    Networkd,
+    NetworkManager,
+    Dhclient,
+    Odhcp6c,
+    Kea,
+    Dnsmasq,
+    Netlink,
+    Static,
+    Exec,
+    Pcap,
+}
+
+// A comma separated list of implementations. Earlier implementations take precedence when several
+// report the same interface.
+#[derive(Debug)]
+struct Implementations(Vec<Implementation>);
+
+impl FromStr for Implementations {
+    type Err = strum::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let implementations = s
+            .split(',')
+            .map(str::trim)
+            .map(Implementation::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self(implementations))
+    }
 }
 
 #[derive(Debug, Envconfig)]
 pub struct Config {
     #[envconfig(from = "DHCP_TEMPLATE__PROVIDER", default = "dhcpcd")]
-    implementation: Implementation,
+    implementations: Implementations,
+
+    #[envconfig(nested)]
+    link: link::Config,
 
     #[envconfig(nested)]
     dhcpcd: dhcpcd::Config,
+
+    #[envconfig(nested)]
+    networkd: networkd::Config,
+
+    #[envconfig(nested)]
+    network_manager: network_manager::Config,
+
+    #[envconfig(nested)]
+    dhclient: dhclient::Config,
+
+    #[envconfig(nested)]
+    odhcp6c: odhcp6c::Config,
+
+    #[envconfig(nested)]
+    kea: kea::Config,
+
+    #[envconfig(nested)]
+    dnsmasq: dnsmasq::Config,
+
+    #[envconfig(nested)]
+    static_file: static_file::Config,
+
+    #[envconfig(nested)]
+    exec: exec::Config,
+
+    #[envconfig(nested)]
+    pcap: pcap::Config,
 }
 
+// Providers report the interfaces and served leases of the node. Its name is set by the agent.
 pub trait Provider
 where
     Self: std::fmt::Debug + Sync + Send,
 {
-    fn interfaces(&self) -> BoxStream<'_, Result<Vec<Interface>>>;
+    fn node(&self) -> BoxStream<'_, Result<Node>>;
 }
 
 impl TryFrom<Config> for Box<dyn Provider> {
@@ -37,9 +118,44 @@ impl TryFrom<Config> for Box<dyn Provider> {
 
     #[instrument(ret(level = Level::DEBUG), err(level = Level::ERROR))]
     fn try_from(config: Config) -> Result<Self, Self::Error> {
-        let provider = Box::new(match config.implementation {
-            Implementation::Dhcpcd => DhcpcdProvider::try_from(config.dhcpcd)?,
-        });
+        let mut providers = config
+            .implementations
+            .0
+            .iter()
+            .map(|implementation| implementation.provider(&config))
+            .collect::<Result<Vec<_>>>()?;
+
+        let provider: Self = if providers.len() == 1 {
+            providers.remove(0)
+        } else {
+            Box::new(CompositeProvider::from(providers))
+        };
+
+        if !config.link.enabled {
+            return Ok(provider);
+        }
+
+        Ok(Box::new(LinkProvider::new(provider, config.link)))
+    }
+}
+
+impl Implementation {
+    fn provider(&self, config: &Config) -> Result<Box<dyn Provider>> {
+        let provider: Box<dyn Provider> = match self {
+            Self::Dhcpcd => Box::new(DhcpcdProvider::try_from(config.dhcpcd.clone())?),
+            Self::Networkd => Box::new(NetworkdProvider::try_from(config.networkd.clone())?),
+            Self::NetworkManager => Box::new(NetworkManagerProvider::try_from(
+                config.network_manager.clone(),
+            )?),
+            Self::Dhclient => Box::new(DhclientProvider::try_from(config.dhclient.clone())?),
+            Self::Odhcp6c => Box::new(Odhcp6cProvider::try_from(config.odhcp6c.clone())?),
+            Self::Kea => Box::new(KeaProvider::try_from(config.kea.clone())?),
+            Self::Dnsmasq => Box::new(DnsmasqProvider::try_from(config.dnsmasq.clone())?),
+            Self::Netlink => Box::new(NetlinkProvider),
+            Self::Static => Box::new(StaticProvider::try_from(config.static_file.clone())?),
+            Self::Exec => Box::new(ExecProvider::try_from(config.exec.clone())?),
+            Self::Pcap => Box::new(PcapProvider::try_from(config.pcap.clone())?),
+        };
 
         Ok(provider)
     }
@@ -0,0 +1,292 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use anyhow::{Context as _, Result};
+use async_stream::try_stream;
+use dhcp_template_api::{Address, Interface, Node, Route};
+use futures_time::{stream::StreamExt as _, time::Duration};
+use futures_util::{Stream, StreamExt as _, TryStreamExt as _, stream::BoxStream};
+use rtnetlink::{
+    Handle, MulticastGroup, RouteMessageBuilder, new_connection, new_multicast_connection,
+    packet_route::{
+        AddressFamily,
+        address::{AddressAttribute, AddressFlags, AddressMessage},
+        link::{LinkAttribute, LinkMessage},
+        route::{RouteAddress, RouteAttribute, RouteHeader, RouteMessage, RouteType},
+    },
+};
+use tracing::{Level, debug, instrument};
+
+use crate::provider::Provider;
+
+const GROUPS: [MulticastGroup; 5] = [
+    MulticastGroup::Link,
+    MulticastGroup::Ipv4Ifaddr,
+    MulticastGroup::Ipv6Ifaddr,
+    MulticastGroup::Ipv4Route,
+    MulticastGroup::Ipv6Route,
+];
+
+const LINK_GROUPS: [MulticastGroup; 3] = [
+    MulticastGroup::Link,
+    MulticastGroup::Ipv4Ifaddr,
+    MulticastGroup::Ipv6Ifaddr,
+];
+
+// Names of the address flags as shown by `ip address`.
+const ADDRESS_FLAGS: [(AddressFlags, &str); 10] = [
+    (AddressFlags::Tentative, "tentative"),
+    (AddressFlags::Deprecated, "deprecated"),
+    (AddressFlags::Homeaddress, "home"),
+    (AddressFlags::Nodad, "nodad"),
+    (AddressFlags::Optimistic, "optimistic"),
+    (AddressFlags::Managetempaddr, "mngtmpaddr"),
+    (AddressFlags::Noprefixroute, "noprefixroute"),
+    (AddressFlags::Mcautojoin, "autojoin"),
+    (AddressFlags::Dadfailed, "dadfailed"),
+    (AddressFlags::StablePrivacy, "stable-privacy"),
+];
+
+// Reads addresses and routes from the kernel of the network namespace the agent runs in, which
+// should be the host network for this provider to be useful.
+#[derive(Debug)]
+pub struct NetlinkProvider;
+
+impl Provider for NetlinkProvider {
+    fn node(&self) -> BoxStream<'_, Result<Node>> {
+        try_stream! {
+            let (connection, handle, messages) = new_multicast_connection(&GROUPS)
+                .context("Could not open netlink socket.")?;
+
+            tokio::spawn(connection);
+            yield read_interfaces(&handle).await?;
+
+            // Messages that are already queued are collected first, because the debounce takes
+            // one per timer.
+            let mut changes = messages.ready_chunks(64).debounce(Duration::from_secs(10));
+
+            while changes.next().await.is_some() {
+                debug!("Change on netlink detected, reloading interfaces.");
+                yield read_interfaces(&handle).await?;
+            }
+        }
+        .map_ok(|interfaces| Node {
+            interfaces,
+            ..Default::default()
+        })
+        .boxed()
+    }
+}
+
+// Changes of links and their addresses, debounced like the changes read by the provider.
+pub fn link_changes() -> impl Stream<Item = Result<()>> {
+    try_stream! {
+        let (connection, _, messages) = new_multicast_connection(&LINK_GROUPS)
+            .context("Could not open netlink socket.")?;
+
+        tokio::spawn(connection);
+
+        let mut changes = messages.ready_chunks(64).debounce(Duration::from_secs(10));
+
+        while changes.next().await.is_some() {
+            debug!("Change of links detected.");
+            yield ();
+        }
+    }
+}
+
+#[instrument(skip_all, err(level = Level::WARN))]
+async fn read_interfaces(handle: &Handle) -> Result<Vec<Interface>> {
+    let mut interfaces: BTreeMap<u32, Interface> = handle
+        .link()
+        .get()
+        .execute()
+        .map_ok(|link| (link.header.index, map_link(&link)))
+        .try_collect()
+        .await
+        .context("Could not read links.")?;
+
+    for (index, addresses) in read_addresses(handle).await? {
+        if let Some(interface) = interfaces.get_mut(&index) {
+            interface.addresses = addresses;
+        }
+    }
+
+    for route in [
+        RouteMessageBuilder::<Ipv4Addr>::new().build(),
+        RouteMessageBuilder::<Ipv6Addr>::new().build(),
+    ] {
+        let mut routes = handle.route().get(route).execute();
+
+        while let Some(message) = routes.try_next().await? {
+            if let Some((index, route)) = map_route(&message)
+                && let Some(interface) = interfaces.get_mut(&index)
+            {
+                interface.routes.push(route);
+            }
+        }
+    }
+
+    let mut interfaces: Vec<_> = interfaces.into_values().collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(interfaces)
+}
+
+// Reads the links of the network namespace once, without watching for changes.
+pub async fn interfaces() -> Result<Vec<Interface>> {
+    let (connection, handle, _) = new_connection().context("Could not open netlink socket.")?;
+
+    tokio::spawn(connection);
+    read_interfaces(&handle).await
+}
+
+async fn read_addresses(handle: &Handle) -> Result<HashMap<u32, Vec<Address>>> {
+    let mut addresses: HashMap<u32, Vec<Address>> = HashMap::new();
+    let mut messages = handle.address().get().execute();
+
+    while let Some(message) = messages
+        .try_next()
+        .await
+        .context("Could not read addresses.")?
+    {
+        if let Some(address) = map_address(&message) {
+            addresses
+                .entry(message.header.index)
+                .or_default()
+                .push(address);
+        }
+    }
+
+    Ok(addresses)
+}
+
+fn map_link(message: &LinkMessage) -> Interface {
+    let mut interface = Interface {
+        ifindex: Some(message.header.index),
+        ..Default::default()
+    };
+
+    for attribute in &message.attributes {
+        match attribute {
+            LinkAttribute::IfName(name) => interface.name.clone_from(name),
+            LinkAttribute::Address(mac) => interface.mac = Some(format_mac(mac)),
+            LinkAttribute::Mtu(mtu) => interface.mtu = Some(*mtu),
+            LinkAttribute::OperState(state) => {
+                interface.operstate = Some(state.to_string().to_lowercase());
+            }
+            _ => {}
+        }
+    }
+
+    if interface.name.is_empty() {
+        interface.name = message.header.index.to_string();
+    }
+
+    interface
+}
+
+fn format_mac(mac: &[u8]) -> String {
+    mac.iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn map_address(message: &AddressMessage) -> Option<Address> {
+    let mut local = None;
+    let mut address = None;
+    let mut flags = None;
+
+    for attribute in &message.attributes {
+        match attribute {
+            AddressAttribute::Local(ip) => local = Some(*ip),
+            AddressAttribute::Address(ip) => address = Some(*ip),
+            AddressAttribute::Flags(value) => flags = Some(*value),
+            _ => {}
+        }
+    }
+
+    // For point-to-point links the address attribute holds the peer, not the local address.
+    let ip = local.or(address)?;
+
+    // The flags attribute supersedes the 8 bit flags of the header.
+    let flags = flags
+        .unwrap_or_else(|| AddressFlags::from_bits_retain(u32::from(message.header.flags.bits())));
+
+    Some(Address {
+        ip: ip.to_string(),
+        len: u32::from(message.header.prefix_len),
+        flags: address_flags(flags, &ip),
+    })
+}
+
+fn address_flags(flags: AddressFlags, ip: &IpAddr) -> Vec<String> {
+    let secondary = if ip.is_ipv6() {
+        "temporary"
+    } else {
+        "secondary"
+    };
+    let dynamic = (!flags.contains(AddressFlags::Permanent)).then_some("dynamic");
+
+    [(AddressFlags::Secondary, secondary)]
+        .into_iter()
+        .chain(ADDRESS_FLAGS)
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, name)| name)
+        .chain(dynamic)
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn map_route(message: &RouteMessage) -> Option<(u32, Route)> {
+    if !matches!(
+        message.header.kind,
+        RouteType::Unicast | RouteType::Unreachable
+    ) {
+        return None;
+    }
+
+    let mut table = u32::from(message.header.table);
+    let mut destination = None;
+    let mut gateway = None;
+    let mut oif = None;
+
+    for attribute in &message.attributes {
+        match attribute {
+            RouteAttribute::Table(value) => table = *value,
+            RouteAttribute::Destination(address) => destination = route_address(address),
+            RouteAttribute::Gateway(address) => gateway = route_address(address),
+            RouteAttribute::Oif(index) => oif = Some(*index),
+            _ => {}
+        }
+    }
+
+    if table != u32::from(RouteHeader::RT_TABLE_MAIN) {
+        return None;
+    }
+
+    // Default routes omit their destination.
+    let destination = destination.unwrap_or(match message.header.address_family {
+        AddressFamily::Inet6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        _ => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+    });
+
+    let route = Route {
+        destination: destination.to_string(),
+        prefix_len: u32::from(message.header.destination_prefix_length),
+        gateway: gateway.map(|gateway| gateway.to_string()),
+    };
+
+    Some((oif?, route))
+}
+
+fn route_address(address: &RouteAddress) -> Option<IpAddr> {
+    match address {
+        RouteAddress::Inet(ip) => Some(IpAddr::V4(*ip)),
+        RouteAddress::Inet6(ip) => Some(IpAddr::V6(*ip)),
+        _ => None,
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, canonicalize},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use dhcp_template_api::Interface;
+use envconfig::Envconfig;
+use tracing::{Level, instrument};
+
+use crate::provider::{
+    dhclient::{merge_interfaces, parse_lease_file},
+    notify::{InterfaceReader, NotifyProvider},
+    sd_lease::parse_lease,
+};
+
+// Lease files are named `<client>-<connection uuid>-<interface>.lease`, where the client is either
+// the internal systemd based client or dhclient.
+const INTERNAL_PREFIX: &str = "internal-";
+const DHCLIENT_PREFIXES: [&str; 2] = ["dhclient-", "dhclient6-"];
+const LEASE_EXTENSION: &str = ".lease";
+const UUID_LEN: usize = 36;
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(
+        from = "DHCP_TEMPLATE__NETWORK_MANAGER_PATH",
+        default = "/var/lib/NetworkManager"
+    )]
+    path: PathBuf,
+}
+
+pub type NetworkManagerProvider = NotifyProvider<NetworkManagerInterfaceReader>;
+
+impl TryFrom<Config> for NetworkManagerProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let path = canonicalize(config.path)?;
+        let provider = Self::new(path, NetworkManagerInterfaceReader);
+
+        Ok(provider)
+    }
+}
+
+#[derive(Debug)]
+pub struct NetworkManagerInterfaceReader;
+
+#[async_trait]
+impl InterfaceReader for NetworkManagerInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn interfaces(&self, path: &Path) -> Result<Vec<Interface>> {
+        let lease_files = fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter_map(|path| lease_file(&path).map(|client| (client, path)));
+
+        let mut interfaces = BTreeMap::new();
+
+        for (client, lease_file) in lease_files {
+            let other = match client {
+                Client::Internal(name) => vec![Interface {
+                    name,
+                    lease4: Some(parse_lease(&lease_file)?),
+                    ..Default::default()
+                }],
+                Client::Dhclient => parse_lease_file(&lease_file)?,
+            };
+
+            merge_interfaces(&mut interfaces, other);
+        }
+
+        Ok(interfaces.into_values().collect())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Client {
+    Internal(String),
+    Dhclient,
+}
+
+fn lease_file(path: &Path) -> Option<Client> {
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())?
+        .strip_suffix(LEASE_EXTENSION)?;
+
+    if DHCLIENT_PREFIXES
+        .iter()
+        .any(|prefix| file_name.starts_with(prefix))
+    {
+        // dhclient lease blocks name their interface themselves.
+        return Some(Client::Dhclient);
+    }
+
+    file_name
+        .strip_prefix(INTERNAL_PREFIX)?
+        .get(UUID_LEN..)
+        .and_then(|s| s.strip_prefix('-'))
+        .filter(|name| !name.is_empty())
+        .map(|name| Client::Internal(name.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::provider::network_manager::{Client, lease_file};
+
+    const UUID: &str = "0b1a8a0e-3c3f-4a8a-9a7e-6f2b0c1d2e3f";
+
+    #[test]
+    fn parses_internal_lease_file() {
+        assert_eq!(
+            lease_file(Path::new(&format!("internal-{UUID}-eth0.lease"))),
+            Some(Client::Internal("eth0".to_owned()))
+        );
+        assert_eq!(
+            lease_file(Path::new(&format!(
+                "/var/lib/NetworkManager/internal-{UUID}-br-lan.lease"
+            ))),
+            Some(Client::Internal("br-lan".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parses_dhclient_lease_files() {
+        assert_eq!(
+            lease_file(Path::new(&format!("dhclient-{UUID}-eth0.lease"))),
+            Some(Client::Dhclient)
+        );
+        assert_eq!(
+            lease_file(Path::new(&format!("dhclient6-{UUID}-eth0.lease"))),
+            Some(Client::Dhclient)
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(
+            lease_file(Path::new(&format!("internal-{UUID}-.lease"))),
+            None
+        );
+        assert_eq!(
+            lease_file(Path::new(&format!("internal-{UUID}.lease"))),
+            None
+        );
+        assert_eq!(lease_file(Path::new("internal-eth0.lease")), None);
+        assert_eq!(
+            lease_file(Path::new(&format!("internal-{UUID}-eth0.conf"))),
+            None
+        );
+        assert_eq!(lease_file(Path::new("timestamps")), None);
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    borrow::ToOwned,
+    collections::{BTreeMap, HashMap},
+    fs::{self, canonicalize, read_to_string},
+    net::Ipv6Addr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use dhcp_template_api::{Interface, Lease6, Prefix6};
+use envconfig::Envconfig;
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::UnixStream,
+};
+use tracing::{Level, instrument};
+
+use crate::provider::{
+    lease_time::{LIFETIME_INFINITE, acquire6},
+    notify::{InterfaceReader, NotifyProvider},
+    sd_lease::parse_lease,
+};
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(
+        from = "DHCP_TEMPLATE__NETWORKD_PATH",
+        default = "/run/systemd/netif/leases"
+    )]
+    path: PathBuf,
+
+    #[envconfig(
+        from = "DHCP_TEMPLATE__NETWORKD_VARLINK_PATH",
+        default = "/run/systemd/netif/io.systemd.Network"
+    )]
+    varlink_path: PathBuf,
+
+    #[envconfig(from = "DHCP_TEMPLATE__NETWORKD_SYSFS_PATH")]
+    sysfs_path: Option<PathBuf>,
+}
+
+pub type NetworkdProvider = NotifyProvider<NetworkdInterfaceReader>;
+
+impl TryFrom<Config> for NetworkdProvider {
+    type Error = anyhow::Error;
+
+    // networkd rewrites the state files of a link next to the leases whenever its DHCPv6 lease
+    // changes, so the whole state directory is watched.
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let leases = canonicalize(config.path)?;
+        let path = leases
+            .parent()
+            .ok_or_else(|| anyhow!("Could not get parent directory of {}.", leases.display()))?
+            .to_path_buf();
+
+        let provider = Self::new(
+            path,
+            NetworkdInterfaceReader {
+                leases,
+                varlink_path: config.varlink_path,
+                sysfs_path: config.sysfs_path,
+            },
+        )
+        .recursive();
+
+        Ok(provider)
+    }
+}
+
+// networkd only persists IPv4 leases (named by link index) to disk. Delegated prefixes and the
+// names of links are read from its varlink interface, which is available since systemd 257.
+#[derive(Debug)]
+pub struct NetworkdInterfaceReader {
+    leases: PathBuf,
+    varlink_path: PathBuf,
+    sysfs_path: Option<PathBuf>,
+}
+
+#[async_trait]
+impl InterfaceReader for NetworkdInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn interfaces(&self, _path: &Path) -> Result<Vec<Interface>> {
+        let links = describe(&self.varlink_path).await.unwrap_or_default();
+        let link_names = if links.is_empty() {
+            self.sysfs_path
+                .as_deref()
+                .map(link_names)
+                .unwrap_or_default()
+        } else {
+            links
+                .iter()
+                .map(|link| (link.index.to_string(), link.name.clone()))
+                .collect()
+        };
+
+        let lease_files = fs::read_dir(&self.leases)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file());
+
+        let mut interfaces = BTreeMap::new();
+
+        for lease_file in lease_files {
+            let index = file_name(&lease_file).ok_or_else(|| {
+                anyhow!(
+                    "Could not extract file name from path {}.",
+                    lease_file.display()
+                )
+            })?;
+
+            let name = link_names.get(&index).cloned().unwrap_or(index);
+            let lease4 = parse_lease(&lease_file)?;
+
+            interfaces.insert(
+                name.clone(),
+                Interface {
+                    name,
+                    lease4: Some(lease4),
+                    ..Default::default()
+                },
+            );
+        }
+
+        for link in links {
+            let Some(lease6) = link.dhcpv6_client.and_then(map_lease6) else {
+                continue;
+            };
+
+            interfaces
+                .entry(link.name.clone())
+                .or_insert_with(|| Interface {
+                    name: link.name,
+                    ..Default::default()
+                })
+                .lease6 = Some(lease6);
+        }
+
+        Ok(interfaces.into_values().collect())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Describe {
+    #[serde(default)]
+    interfaces: Vec<Link>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Link {
+    index: u32,
+    name: String,
+    #[serde(rename = "DHCPv6Client")]
+    dhcpv6_client: Option<Dhcp6Client>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Dhcp6Client {
+    lease: Option<Dhcp6Lease>,
+    #[serde(default)]
+    prefixes: Vec<Dhcp6Prefix>,
+}
+
+// Timestamps are in microseconds of the realtime clock.
+#[derive(Debug, Deserialize)]
+struct Dhcp6Lease {
+    #[serde(rename = "LeaseTimestampUSec")]
+    timestamp: Option<u64>,
+    #[serde(rename = "Timeout1USec")]
+    t1: Option<u64>,
+    #[serde(rename = "Timeout2USec")]
+    t2: Option<u64>,
+}
+
+// Infinite lifetimes are omitted.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Dhcp6Prefix {
+    prefix: Vec<u8>,
+    prefix_length: u32,
+    #[serde(rename = "PreferredLifetimeUSec")]
+    preferred: Option<u64>,
+    #[serde(rename = "ValidLifetimeUSec")]
+    valid: Option<u64>,
+}
+
+// Calls `io.systemd.Network.Describe`, where messages are json terminated by a null byte.
+#[instrument(ret(level = Level::DEBUG), err(level = Level::DEBUG))]
+async fn describe(varlink_path: &Path) -> Result<Vec<Link>> {
+    #[derive(Deserialize)]
+    struct Reply {
+        parameters: Option<Describe>,
+        error: Option<String>,
+    }
+
+    let mut stream = UnixStream::connect(varlink_path).await?;
+    stream
+        .write_all(b"{\"method\":\"io.systemd.Network.Describe\",\"parameters\":{}}\0")
+        .await?;
+
+    let mut message = Vec::new();
+    BufReader::new(stream).read_until(0, &mut message).await?;
+    message.pop_if(|byte| *byte == 0);
+
+    let reply: Reply = serde_json::from_slice(&message)?;
+
+    if let Some(error) = reply.error {
+        bail!("Could not describe links: {error}.");
+    }
+
+    Ok(reply
+        .parameters
+        .map(|describe| describe.interfaces)
+        .unwrap_or_default())
+}
+
+fn map_lease6(client: Dhcp6Client) -> Option<Lease6> {
+    let lease = client.lease?;
+    let timestamp = lease.timestamp?;
+    let seconds = |usec: Option<u64>| {
+        usec.map_or(Some(LIFETIME_INFINITE), |usec| {
+            u32::try_from(usec.saturating_sub(timestamp) / 1_000_000).ok()
+        })
+    };
+
+    let prefix6: Vec<_> = client
+        .prefixes
+        .iter()
+        .filter_map(|prefix| {
+            let ip: [u8; 16] = prefix.prefix.as_slice().try_into().ok()?;
+
+            Some(Prefix6 {
+                ip: Ipv6Addr::from(ip).to_string(),
+                len: prefix.prefix_length,
+                preferred_lifetime_seconds: seconds(prefix.preferred),
+                valid_lifetime_seconds: seconds(prefix.valid),
+                renewal_time_seconds: seconds(lease.t1),
+                rebinding_time_seconds: seconds(lease.t2),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    if prefix6.is_empty() {
+        return None;
+    }
+
+    let mut lease6 = Lease6 {
+        prefix6,
+        ..Default::default()
+    };
+
+    acquire6(&mut lease6, timestamp / 1_000_000);
+
+    Some(lease6)
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .map(ToOwned::to_owned)
+}
+
+// Indexes are only meaningful in the network namespace of the host, so this fallback needs the
+// sysfs of the host, either through `hostNetwork` or a mount. Links that cannot be resolved keep
+// their index as name.
+fn link_names(sysfs_path: &Path) -> HashMap<String, String> {
+    let Ok(entries) = fs::read_dir(sysfs_path) else {
+        return HashMap::default();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let index = read_to_string(entry.path().join("ifindex")).ok()?;
+            let name = entry.file_name().into_string().ok()?;
+
+            Some((index.trim().to_owned(), name))
+        })
+        .collect()
+}
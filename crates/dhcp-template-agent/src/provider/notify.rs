@@ -1,35 +1,67 @@
-use std::path::{Path, PathBuf};
+use std::{
+    future::pending,
+    path::{Path, PathBuf},
+    pin::pin,
+    time::Duration as StdDuration,
+};
 
 use anyhow::{Context, Result};
-use async_stream::stream;
+use async_stream::{stream, try_stream};
 use async_trait::async_trait;
-use dhcp_template_api::Interface;
+use dhcp_template_api::{Interface, Node, ServedLease};
 use futures_time::{stream::StreamExt as _, time::Duration};
 use futures_util::{
     Stream, StreamExt as _, TryStreamExt,
     future::{Ready, ready},
-    stream::{BoxStream, once},
+    stream::BoxStream,
 };
 use notify::{Event, EventKind, RecursiveMode, Watcher, recommended_watcher};
-use tokio::sync::mpsc::channel;
+use tokio::{select, sync::mpsc::channel, time::sleep};
 use tracing::{debug, error};
 
-use crate::provider::Provider;
+use crate::provider::{
+    Provider,
+    lease_time::{next_expiry, now},
+};
 
 #[async_trait]
 pub trait InterfaceReader {
-    async fn interfaces(&self, path: &Path) -> Result<Vec<Interface>>;
+    async fn interfaces(&self, _path: &Path) -> Result<Vec<Interface>> {
+        Ok(Vec::new())
+    }
+
+    async fn served(&self, _path: &Path) -> Result<Vec<ServedLease>> {
+        Ok(Vec::new())
+    }
+
+    async fn node(&self, path: &Path) -> Result<Node> {
+        Ok(Node {
+            interfaces: self.interfaces(path).await?,
+            served: self.served(path).await?,
+            ..Default::default()
+        })
+    }
 }
 
 #[derive(Debug)]
 pub struct NotifyProvider<R> {
     path: PathBuf,
+    mode: RecursiveMode,
     reader: R,
 }
 
 impl<R> NotifyProvider<R> {
     pub fn new(path: PathBuf, reader: R) -> Self {
-        Self { path, reader }
+        Self {
+            path,
+            mode: RecursiveMode::NonRecursive,
+            reader,
+        }
+    }
+
+    pub fn recursive(mut self) -> Self {
+        self.mode = RecursiveMode::Recursive;
+        self
     }
 }
 
@@ -37,21 +69,59 @@ impl<R> Provider for NotifyProvider<R>
 where
     R: InterfaceReader + Sync + Send + std::fmt::Debug,
 {
-    fn interfaces(&self) -> BoxStream<'_, Result<Vec<Interface>>> {
-        let initial = once(async { Ok(Event::new(EventKind::Other)) });
-        let changes = watch_path(&self.path)
-            .try_filter(is_relevant_event)
-            .into_stream()
-            .debounce(Duration::from_secs(10))
-            .inspect_ok(|_| debug!("Change on filesystem detected, reloading interfaces."));
-
-        initial
-            .chain(changes)
-            .and_then(async |_| self.reader.interfaces(&self.path).await)
-            .boxed()
+    // Besides changes on the filesystem, the node is reloaded when its earliest lease expires, so
+    // expired leases are dropped even if the client does not touch its files.
+    fn node(&self) -> BoxStream<'_, Result<Node>> {
+        try_stream! {
+            let mut changes = pin!(changes(&self.path, self.mode));
+
+            loop {
+                let node = self.reader.node(&self.path).await?;
+                let now = now()?;
+                let expiry = next_expiry(&node, now)
+                    .map(|expires_at| StdDuration::from_secs(expires_at - now));
+
+                yield node;
+
+                let change = select! {
+                    change = changes.next() => change,
+                    () = sleep_some(expiry) => {
+                        debug!("Lease expired, reloading interfaces.");
+                        continue;
+                    }
+                };
+
+                let Some(change) = change else {
+                    break;
+                };
+
+                change?;
+            }
+        }
+        .boxed()
     }
 }
 
+// Sleeps until the next lease expires, or forever if no lease does.
+async fn sleep_some(duration: Option<StdDuration>) {
+    match duration {
+        Some(duration) => sleep(duration).await,
+        None => pending().await,
+    }
+}
+
+// The debounce takes a single event from the stream whenever its timer expires, so events that are
+// already queued are collected first. Otherwise a burst of events, like the rename of a mounted
+// ConfigMap, would be reported one timer after another.
+pub fn changes(path: &Path, mode: RecursiveMode) -> impl Stream<Item = Result<Event>> {
+    watch_path(path, mode)
+        .try_filter(is_relevant_event)
+        .ready_chunks(64)
+        .filter_map(|events| ready(events.into_iter().reduce(Result::and)))
+        .debounce(Duration::from_secs(10))
+        .inspect_ok(|_| debug!("Change on filesystem detected, reloading interfaces."))
+}
+
 fn is_relevant_event(event: &Event) -> Ready<bool> {
     ready(matches!(
         event.kind,
@@ -59,7 +129,7 @@ fn is_relevant_event(event: &Event) -> Ready<bool> {
     ))
 }
 
-fn watch_path(path: &Path) -> impl Stream<Item = Result<Event>> {
+fn watch_path(path: &Path, mode: RecursiveMode) -> impl Stream<Item = Result<Event>> {
     let (tx, mut rx) = channel(64);
 
     stream! {
@@ -70,7 +140,7 @@ fn watch_path(path: &Path) -> impl Stream<Item = Result<Event>> {
         })?;
 
         watcher
-            .watch(path, RecursiveMode::NonRecursive)
+            .watch(path, mode)
             .with_context(|| format!("Could not watch path {}.", path.display()))?;
 
         debug!("Watching {} for changes.", path.display());
@@ -0,0 +1,191 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, canonicalize, read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use dhcp_template_api::{Address6, Interface, Lease6, Prefix6};
+use envconfig::Envconfig;
+use serde::Deserialize;
+use tracing::{Level, instrument};
+
+use crate::provider::{
+    lease_time::{acquire6, modified},
+    notify::{InterfaceReader, NotifyProvider},
+};
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(
+        from = "DHCP_TEMPLATE__ODHCP6C_PATH",
+        default = "/run/dhcp-template/odhcp6c"
+    )]
+    path: PathBuf,
+}
+
+pub type Odhcp6cProvider = NotifyProvider<Odhcp6cInterfaceReader>;
+
+impl TryFrom<Config> for Odhcp6cProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let path = canonicalize(config.path)?;
+        let provider = Self::new(path, Odhcp6cInterfaceReader);
+
+        Ok(provider)
+    }
+}
+
+// Reads the state files written by `hooks/odhcp6c.sh`. The hook passes the environment of
+// odhcp6c through unchanged, so the variables are parsed here.
+#[derive(Debug)]
+pub struct Odhcp6cInterfaceReader;
+
+#[async_trait]
+impl InterfaceReader for Odhcp6cInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn interfaces(&self, path: &Path) -> Result<Vec<Interface>> {
+        let state_files = fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_state_file(path));
+
+        let mut interfaces = BTreeMap::new();
+
+        for state_file in state_files {
+            let state = parse_state(&state_file)?;
+            let mut lease6 = Lease6 {
+                dns: split(&state.rdnss),
+                prefix6: get_prefix(&state.prefixes),
+                address6: get_address(&state.addresses),
+                domain_search: split(&state.domains),
+                ntp: split(&state.ntp_ip)
+                    .into_iter()
+                    .chain(split(&state.ntp_fqdn))
+                    .collect(),
+                ..Default::default()
+            };
+
+            // Lifetimes count down from the time the hook wrote the state file.
+            acquire6(&mut lease6, modified(&state_file)?);
+
+            interfaces.insert(
+                state.interface.clone(),
+                Interface {
+                    name: state.interface,
+                    lease6: Some(lease6),
+                    ..Default::default()
+                },
+            );
+        }
+
+        Ok(interfaces.into_values().collect())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct State {
+    interface: String,
+    #[serde(default)]
+    prefixes: String,
+    #[serde(default)]
+    rdnss: String,
+    #[serde(default)]
+    addresses: String,
+    #[serde(default)]
+    domains: String,
+    #[serde(default)]
+    ntp_ip: String,
+    #[serde(default)]
+    ntp_fqdn: String,
+}
+
+fn is_state_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| matches!(ext.to_str(), Some("json")))
+}
+
+#[instrument(ret(level = Level::DEBUG), err(level = Level::WARN))]
+fn parse_state(path: &Path) -> Result<State> {
+    let bytes = read(path)?;
+    let state = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Could not parse odhcp6c state {}.", path.display()))?;
+
+    Ok(state)
+}
+
+fn split(list: &str) -> Vec<String> {
+    list.split_whitespace().map(ToOwned::to_owned).collect()
+}
+
+// Prefixes and addresses are formatted as `<ip>/<len>,<preferred>,<valid>[,<key>=<value>...]`,
+// odhcp6c does not pass T1 and T2.
+fn get_prefix(prefixes: &str) -> Vec<Prefix6> {
+    prefixes
+        .split_whitespace()
+        .filter_map(parse_entry)
+        .filter_map(|(ip, len, preferred, valid)| {
+            Some(Prefix6 {
+                ip: ip.to_owned(),
+                len: len.parse().ok()?,
+                preferred_lifetime_seconds: preferred,
+                valid_lifetime_seconds: valid,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+fn get_address(addresses: &str) -> Vec<Address6> {
+    addresses
+        .split_whitespace()
+        .filter_map(parse_entry)
+        .map(|(ip, _, preferred, valid)| Address6 {
+            ip: ip.to_owned(),
+            preferred_lifetime_seconds: preferred,
+            valid_lifetime_seconds: valid,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn parse_entry(entry: &str) -> Option<(&str, &str, Option<u32>, Option<u32>)> {
+    let mut fields = entry.split(',');
+    let (ip, len) = fields.next()?.split_once('/')?;
+    let preferred = fields.next().and_then(|value| value.parse().ok());
+    let valid = fields.next().and_then(|value| value.parse().ok());
+
+    Some((ip, len, preferred, valid))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::{Context as _, Result};
+
+    use crate::provider::{notify::InterfaceReader as _, odhcp6c::Odhcp6cInterfaceReader};
+
+    #[tokio::test]
+    async fn reads_state_file() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/odhcp6c");
+        let interfaces = Odhcp6cInterfaceReader.interfaces(&path).await?;
+
+        let [interface] = interfaces.as_slice() else {
+            panic!("Expected a single interface, got {interfaces:?}.");
+        };
+
+        assert_eq!(interface.name, "eth0");
+
+        let lease6 = interface.lease6.as_ref().context("Missing lease6.")?;
+        assert_eq!(lease6.dns, ["2001:db8::53", "2001:db8::54"]);
+        assert_eq!(lease6.domain_search, ["example.com"]);
+        assert_eq!(lease6.ntp, ["2001:db8::123", "ntp.example.com"]);
+        assert_eq!(lease6.prefix6.len(), 1);
+        assert_eq!(lease6.address6.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,476 @@
+use std::{
+    collections::BTreeMap,
+    fs::read,
+    io::Cursor,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, bail};
+use async_stream::try_stream;
+use dhcp_template_api::{Interface, Node};
+use dhcproto::{Decodable as _, Decoder, v4, v6};
+use envconfig::Envconfig;
+use futures_util::{StreamExt as _, future::pending, stream::BoxStream};
+use pcap_file::{
+    DataLink,
+    pcap::PcapReader,
+    pcapng::{Block, PcapNgReader, blocks::interface_description::InterfaceDescriptionOption},
+};
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::provider::{
+    Provider,
+    dhcpcd::{self, Lease},
+    lease_time::{acquire4, acquire6},
+};
+
+const PCAPNG_MAGIC: [u8; 4] = [0x0a, 0x0d, 0x0d, 0x0a];
+// Microseconds, unless the interface description says otherwise.
+const DEFAULT_TSRESOL: u8 = 6;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: [u16; 2] = [0x8100, 0x88a8];
+const PROTOCOL_UDP: u8 = 17;
+
+const PORT_SERVER4: u16 = 67;
+const PORT_CLIENT4: u16 = 68;
+const PORT_SERVER6: u16 = 547;
+const PORT_CLIENT6: u16 = 546;
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(from = "DHCP_TEMPLATE__PCAP_PATH")]
+    path: Option<PathBuf>,
+
+    #[envconfig(from = "DHCP_TEMPLATE__PCAP_INTERFACE", default = "pcap")]
+    interface: String,
+
+    #[envconfig(from = "DHCP_TEMPLATE__PCAP_REALTIME", default = "false")]
+    realtime: bool,
+}
+
+// Replays the dhcp replies of a pcap or pcapng capture, which makes lease ingestion reproducible
+// without a dhcp server. Interfaces are named after the capture interface if the file records it.
+// With realtime enabled, the gaps between packets are kept, otherwise the capture replays at once.
+#[derive(Debug)]
+pub struct PcapProvider {
+    path: PathBuf,
+    interface: String,
+    realtime: bool,
+}
+
+impl TryFrom<Config> for PcapProvider {
+    type Error = anyhow::Error;
+
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let Some(path) = config.path else {
+            bail!("DHCP_TEMPLATE__PCAP_PATH is required for the pcap provider.");
+        };
+
+        let provider = Self {
+            path,
+            interface: config.interface,
+            realtime: config.realtime,
+        };
+
+        Ok(provider)
+    }
+}
+
+impl Provider for PcapProvider {
+    fn node(&self) -> BoxStream<'_, Result<Node>> {
+        try_stream! {
+            let packets = read_packets(&self.path)
+                .with_context(|| format!("Could not read capture {}.", self.path.display()))?;
+
+            let mut interfaces = BTreeMap::new();
+            let mut previous = None;
+
+            for packet in packets {
+                let lease = match decode_packet(&packet) {
+                    Ok(Some(lease)) => lease,
+                    Ok(None) => continue,
+                    Err(error) => {
+                        warn!("Skipping packet at {:?}: {error:#}", packet.timestamp);
+                        continue;
+                    }
+                };
+
+                if self.realtime && let Some(previous) = previous {
+                    sleep(packet.timestamp.saturating_sub(previous)).await;
+                }
+
+                previous = Some(packet.timestamp);
+
+                let name = packet.interface.unwrap_or_else(|| self.interface.clone());
+                let interface = interfaces.entry(name.clone()).or_insert(Interface {
+                    name,
+                    ..Default::default()
+                });
+
+                let acquired_at = packet.timestamp.as_secs();
+
+                match lease {
+                    Lease::V4(mut lease4) => {
+                        acquire4(&mut lease4, acquired_at);
+                        interface.lease4 = Some(lease4);
+                    }
+                    Lease::V6(mut lease6) => {
+                        acquire6(&mut lease6, acquired_at);
+                        interface.lease6 = Some(lease6);
+                    }
+                }
+
+                yield Node {
+                    interfaces: interfaces.values().cloned().collect(),
+                    ..Default::default()
+                };
+            }
+
+            // The agent treats a finished provider as an error, so keep the last state instead.
+            debug!("Capture replayed, keeping the last state.");
+            pending::<()>().await;
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+struct Packet {
+    timestamp: Duration,
+    interface: Option<String>,
+    datalink: DataLink,
+    data: Vec<u8>,
+}
+
+fn read_packets(path: &Path) -> Result<Vec<Packet>> {
+    let bytes = read(path)?;
+
+    if bytes.starts_with(&PCAPNG_MAGIC) {
+        read_pcapng(&bytes)
+    } else {
+        read_pcap(&bytes)
+    }
+}
+
+fn read_pcap(bytes: &[u8]) -> Result<Vec<Packet>> {
+    let mut reader = PcapReader::new(Cursor::new(bytes))?;
+    let datalink = reader.header().datalink;
+    let mut packets = Vec::new();
+
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet?;
+
+        packets.push(Packet {
+            timestamp: packet.timestamp,
+            interface: None,
+            datalink,
+            data: packet.data.into_owned(),
+        });
+    }
+
+    Ok(packets)
+}
+
+fn read_pcapng(bytes: &[u8]) -> Result<Vec<Packet>> {
+    let mut reader = PcapNgReader::new(Cursor::new(bytes))?;
+    let mut interfaces = Vec::new();
+    let mut packets = Vec::new();
+
+    while let Some(block) = reader.next_block() {
+        match block? {
+            // Interface ids are scoped to their section.
+            Block::SectionHeader(_) => interfaces.clear(),
+            Block::InterfaceDescription(description) => {
+                let name = description.options.iter().find_map(|option| match option {
+                    InterfaceDescriptionOption::IfName(name) => Some(name.to_string()),
+                    _ => None,
+                });
+
+                let resolution = description
+                    .options
+                    .iter()
+                    .find_map(|option| match option {
+                        InterfaceDescriptionOption::IfTsResol(resolution) => Some(*resolution),
+                        _ => None,
+                    })
+                    .unwrap_or(DEFAULT_TSRESOL);
+
+                interfaces.push((description.linktype, name, resolution));
+            }
+            Block::EnhancedPacket(packet) => {
+                let Some((datalink, name, resolution)) = usize::try_from(packet.interface_id)
+                    .ok()
+                    .and_then(|index| interfaces.get(index))
+                else {
+                    bail!("Unknown interface id {}.", packet.interface_id);
+                };
+
+                // The reader takes the raw timestamp for nanoseconds, whatever the resolution of
+                // the interface is.
+                let Some(timestamp) = timestamp(packet.timestamp.as_nanos(), *resolution) else {
+                    bail!("Unsupported timestamp resolution {resolution}.");
+                };
+
+                packets.push(Packet {
+                    timestamp,
+                    interface: name.clone(),
+                    datalink: *datalink,
+                    data: packet.data.into_owned(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(packets)
+}
+
+// A resolution with the high bit set is a negative power of two, otherwise of ten.
+fn timestamp(ticks: u128, resolution: u8) -> Option<Duration> {
+    let per_second = if resolution & 0x80 == 0 {
+        10u128.checked_pow(u32::from(resolution))?
+    } else {
+        1u128.checked_shl(u32::from(resolution & 0x7f))?
+    };
+
+    let nanos = (ticks % per_second).checked_mul(1_000_000_000)? / per_second;
+
+    Some(Duration::new(
+        u64::try_from(ticks / per_second).ok()?,
+        u32::try_from(nanos).ok()?,
+    ))
+}
+
+// Only replies of a server are leases, requests of the client are ignored.
+fn decode_packet(packet: &Packet) -> Result<Option<Lease>> {
+    let Some((source, destination, payload)) = udp(packet.datalink, &packet.data) else {
+        return Ok(None);
+    };
+
+    let lease = match (source, destination) {
+        (PORT_SERVER4, PORT_CLIENT4) => {
+            let message = v4::Message::decode(&mut Decoder::new(payload))
+                .context("Could not decode dhcpv4 message.")?;
+
+            (message.opts().msg_type() == Some(v4::MessageType::Ack))
+                .then(|| Lease::V4(dhcpcd::v4::from_message(&message)))
+        }
+        (PORT_SERVER6, PORT_CLIENT6) => {
+            let message = v6::Message::decode(&mut Decoder::new(payload))
+                .context("Could not decode dhcpv6 message.")?;
+
+            (message.msg_type() == v6::MessageType::Reply)
+                .then(|| Lease::V6(dhcpcd::v6::from_message(&message)))
+        }
+        _ => None,
+    };
+
+    Ok(lease)
+}
+
+// Strips the link, network and transport headers, returning the udp ports and payload.
+fn udp(datalink: DataLink, data: &[u8]) -> Option<(u16, u16, &[u8])> {
+    let (ethertype, data) = match datalink {
+        DataLink::ETHERNET => {
+            let mut ethertype = be_u16(data, 12)?;
+            let mut offset = 14;
+
+            while ETHERTYPE_VLAN.contains(&ethertype) {
+                ethertype = be_u16(data, offset + 2)?;
+                offset += 4;
+            }
+
+            (ethertype, data.get(offset..)?)
+        }
+        DataLink::LINUX_SLL => (be_u16(data, 14)?, data.get(16..)?),
+        DataLink::LINUX_SLL2 => (be_u16(data, 0)?, data.get(20..)?),
+        DataLink::RAW | DataLink::IPV4 | DataLink::IPV6 => match data.first()? >> 4 {
+            4 => (ETHERTYPE_IPV4, data),
+            6 => (ETHERTYPE_IPV6, data),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let segment = match ethertype {
+        ETHERTYPE_IPV4 if *data.get(9)? == PROTOCOL_UDP => {
+            data.get(usize::from(data.first()? & 0x0f) * 4..)?
+        }
+        // Extension headers are not followed, dhcp replies do not carry them in practice.
+        ETHERTYPE_IPV6 if *data.get(6)? == PROTOCOL_UDP => data.get(40..)?,
+        _ => return None,
+    };
+
+    Some((be_u16(segment, 0)?, be_u16(segment, 2)?, segment.get(8..)?))
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)?
+        .try_into()
+        .ok()
+        .map(u16::from_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use anyhow::Result;
+    use pcap_file::DataLink;
+
+    use crate::provider::{
+        dhcpcd::Lease,
+        pcap::{Packet, decode_packet, read_packets, timestamp, udp},
+    };
+
+    const PAYLOAD: [u8; 4] = [0xde, 0xad, 0xbe, 0xef];
+
+    // An ipv4 header with the given number of 32 bit words, followed by a udp segment.
+    fn ipv4(words: u8) -> Vec<u8> {
+        let mut data = vec![0; usize::from(words) * 4];
+        data[0] = 0x40 | words;
+        data[9] = 17;
+        data.extend([0, 67, 0, 68, 0, 12, 0, 0]);
+        data.extend(PAYLOAD);
+        data
+    }
+
+    fn fixture(name: &str) -> Result<Vec<Packet>> {
+        read_packets(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("fixtures/pcap")
+                .join(name),
+        )
+    }
+
+    fn assert_leases(packets: &[Packet]) -> Result<()> {
+        let [ack, reply] = packets else {
+            panic!("Expected two packets, got {packets:?}.");
+        };
+
+        assert_eq!(ack.timestamp, Duration::from_secs(1_700_000_000));
+        assert_eq!(reply.timestamp, Duration::from_secs(1_700_000_010));
+
+        let Some(Lease::V4(lease4)) = decode_packet(ack)? else {
+            panic!("Expected a v4 lease.");
+        };
+
+        assert_eq!(lease4.address.as_deref(), Some("192.0.2.10"));
+
+        let Some(Lease::V6(lease6)) = decode_packet(reply)? else {
+            panic!("Expected a v6 lease.");
+        };
+
+        assert_eq!(lease6.prefix6.len(), 1);
+        assert_eq!(lease6.prefix6[0].ip, "2001:db8:1::");
+
+        Ok(())
+    }
+
+    #[test]
+    fn reads_pcap() -> Result<()> {
+        let packets = fixture("dhcp.pcap")?;
+
+        assert!(packets.iter().all(|packet| packet.interface.is_none()));
+        assert!(
+            packets
+                .iter()
+                .all(|packet| packet.datalink == DataLink::ETHERNET)
+        );
+
+        assert_leases(&packets)
+    }
+
+    #[test]
+    fn reads_pcapng_interfaces() -> Result<()> {
+        let packets = fixture("dhcp.pcapng")?;
+
+        let interfaces: Vec<_> = packets
+            .iter()
+            .map(|packet| (packet.interface.as_deref(), packet.datalink))
+            .collect();
+
+        assert_eq!(
+            interfaces,
+            [
+                (Some("eth0"), DataLink::LINUX_SLL),
+                (Some("eth1"), DataLink::LINUX_SLL2)
+            ]
+        );
+
+        assert_leases(&packets)
+    }
+
+    #[test]
+    fn scales_pcapng_timestamps() {
+        let expected = Some(Duration::from_millis(1500));
+
+        assert_eq!(timestamp(1_500_000, 6), expected);
+        assert_eq!(timestamp(1_500_000_000, 9), expected);
+        assert_eq!(timestamp(3, 0x81), expected);
+        assert_eq!(timestamp(1, 127), None);
+    }
+
+    #[test]
+    fn strips_ethernet_headers() {
+        let mut frame = vec![0; 12];
+        frame.extend([0x08, 0x00]);
+        frame.extend(ipv4(5));
+
+        assert_eq!(
+            udp(DataLink::ETHERNET, &frame),
+            Some((67, 68, PAYLOAD.as_slice()))
+        );
+
+        let mut frame = vec![0; 12];
+        frame.extend([0x88, 0xa8, 0, 10, 0x81, 0x00, 0, 20, 0x08, 0x00]);
+        frame.extend(ipv4(5));
+
+        assert_eq!(
+            udp(DataLink::ETHERNET, &frame),
+            Some((67, 68, PAYLOAD.as_slice()))
+        );
+    }
+
+    #[test]
+    fn strips_linux_cooked_headers() {
+        let mut frame = vec![0; 14];
+        frame.extend([0x08, 0x00]);
+        frame.extend(ipv4(5));
+
+        assert_eq!(
+            udp(DataLink::LINUX_SLL, &frame),
+            Some((67, 68, PAYLOAD.as_slice()))
+        );
+
+        let mut frame = vec![0x08, 0x00];
+        frame.extend([0; 18]);
+        frame.extend(ipv4(5));
+
+        assert_eq!(
+            udp(DataLink::LINUX_SLL2, &frame),
+            Some((67, 68, PAYLOAD.as_slice()))
+        );
+    }
+
+    #[test]
+    fn strips_ipv4_options() {
+        assert_eq!(
+            udp(DataLink::RAW, &ipv4(7)),
+            Some((67, 68, PAYLOAD.as_slice()))
+        );
+    }
+
+    #[test]
+    fn skips_other_protocols() {
+        let mut packet = ipv4(5);
+        packet[9] = 6;
+
+        assert_eq!(udp(DataLink::RAW, &packet), None);
+        assert_eq!(udp(DataLink::RAW, &ipv4(5)[..20]), None);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{borrow::ToOwned, collections::HashMap, fs::read_to_string, net::Ipv4Addr, path::Path};
+
+use anyhow::Result;
+use dhcp_template_api::{Lease4, Route};
+use tracing::{Level, instrument};
+
+use crate::provider::lease_time::{acquire4, modified};
+
+// Lease files written by the systemd dhcp client, which is shared by systemd-networkd and the
+// internal client of NetworkManager.
+#[instrument(ret(level = Level::DEBUG), err(level = Level::WARN))]
+pub fn parse_lease(path: &Path) -> Result<Lease4> {
+    let content = read_to_string(path)?;
+    let values = parse_env(&content);
+
+    let mut lease = Lease4 {
+        dns: values
+            .get("DNS")
+            .map(|dns| dns.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default(),
+        domain: values.get("DOMAINNAME").map(|domain| (*domain).to_owned()),
+        address: values.get("ADDRESS").map(|address| (*address).to_owned()),
+        prefix_len: values
+            .get("NETMASK")
+            .and_then(|mask| mask.parse::<Ipv4Addr>().ok())
+            .map(|mask| u32::from(mask).leading_ones()),
+        routers: values
+            .get("ROUTER")
+            .map(|routers| routers.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default(),
+        server_id: values
+            .get("SERVER_ADDRESS")
+            .map(|server| (*server).to_owned()),
+        lease_time_seconds: values.get("LIFETIME").and_then(|value| value.parse().ok()),
+        renewal_time_seconds: values.get("T1").and_then(|value| value.parse().ok()),
+        rebinding_time_seconds: values.get("T2").and_then(|value| value.parse().ok()),
+        domain_search: values
+            .get("DOMAIN_SEARCH_LIST")
+            .map(|domains| domains.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default(),
+        ntp: values
+            .get("NTP")
+            .map(|ntp| ntp.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default(),
+        mtu: values.get("MTU").and_then(|value| value.parse().ok()),
+        routes: values
+            .get("CLASSLESS_ROUTES")
+            .map(|routes| routes.split_whitespace().filter_map(parse_route).collect())
+            .unwrap_or_default(),
+        // Only private options are written as `OPTION_<code>=<hex>`.
+        options: values
+            .iter()
+            .filter_map(|(key, value)| {
+                let code = key.strip_prefix("OPTION_")?.parse().ok()?;
+                Some((code, value.to_ascii_lowercase()))
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    acquire4(&mut lease, modified(path)?);
+
+    Ok(lease)
+}
+
+// Routes are written as `<destination>/<len>,<gateway>`.
+fn parse_route(route: &str) -> Option<Route> {
+    let (destination, gateway) = route.split_once(',')?;
+    let (destination, prefix_len) = destination.split_once('/')?;
+    let gateway = gateway.parse::<Ipv4Addr>().ok()?;
+
+    Some(Route {
+        destination: destination.to_owned(),
+        prefix_len: prefix_len.parse().ok()?,
+        gateway: (!gateway.is_unspecified()).then(|| gateway.to_string()),
+    })
+}
+
+fn parse_env(content: &str) -> HashMap<&str, &str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+
+    use crate::provider::sd_lease::parse_lease;
+
+    #[test]
+    fn parses_networkd_lease() -> Result<()> {
+        let lease4 =
+            parse_lease(&Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/networkd/2"))?;
+
+        assert_eq!(lease4.address.as_deref(), Some("192.0.2.10"));
+        assert_eq!(lease4.prefix_len, Some(24));
+        assert_eq!(lease4.dns, ["192.0.2.53", "192.0.2.54"]);
+        assert_eq!(lease4.domain_search, ["example.com", "lab.example.com"]);
+        assert_eq!(lease4.ntp, ["192.0.2.123"]);
+        assert_eq!(
+            lease4.options.get(&224).map(String::as_str),
+            Some("c0000201")
+        );
+
+        Ok(())
+    }
+}
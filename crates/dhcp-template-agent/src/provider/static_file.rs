@@ -0,0 +1,75 @@
+use std::{
+    fs::read,
+    path::{Path, PathBuf, absolute},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use dhcp_template_api::Node;
+use envconfig::Envconfig;
+use tracing::{Level, instrument};
+
+use crate::provider::notify::{InterfaceReader, NotifyProvider};
+
+#[derive(Debug, Clone, Envconfig)]
+pub struct Config {
+    #[envconfig(
+        from = "DHCP_TEMPLATE__STATIC_PATH",
+        default = "/etc/dhcp-template/node.yaml"
+    )]
+    path: PathBuf,
+}
+
+pub type StaticProvider = NotifyProvider<StaticInterfaceReader>;
+
+impl TryFrom<Config> for StaticProvider {
+    type Error = anyhow::Error;
+
+    // Editors usually replace files instead of writing them in place, so the parent directory is
+    // watched rather than the file itself. The path is not canonicalized, because a mounted ConfigMap
+    // links it into a directory that is swapped on every update.
+    fn try_from(config: Config) -> Result<Self, Self::Error> {
+        let file = absolute(config.path)?;
+        let path = file
+            .parent()
+            .ok_or_else(|| anyhow!("Could not get parent directory of {}.", file.display()))?
+            .to_path_buf();
+
+        let provider = Self::new(path, StaticInterfaceReader { file });
+
+        Ok(provider)
+    }
+}
+
+#[derive(Debug)]
+pub struct StaticInterfaceReader {
+    file: PathBuf,
+}
+
+#[async_trait]
+impl InterfaceReader for StaticInterfaceReader {
+    #[instrument(skip(self), err(level = Level::WARN))]
+    async fn node(&self, _path: &Path) -> Result<Node> {
+        parse_node(&self.file)
+    }
+}
+
+// The file mirrors the `Node` message. Its name is ignored in favor of the name of the agent.
+#[instrument(ret(level = Level::DEBUG), err(level = Level::WARN))]
+fn parse_node(path: &Path) -> Result<Node> {
+    let bytes = read(path)?;
+    let context = || format!("Could not parse static node {}.", path.display());
+
+    let node = if is_json_file(path) {
+        serde_json::from_slice(&bytes).with_context(context)?
+    } else {
+        serde_yaml::from_slice(&bytes).with_context(context)?
+    };
+
+    Ok(node)
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| matches!(ext.to_str(), Some("json")))
+}
@@ -12,6 +12,9 @@ impl ShallowClone for Update {
                 Some(Data::Full(node)) => Some(Data::Shallow(Shallow {
                     name: node.name.clone(),
                 })),
+                Some(Data::Delta(delta)) => Some(Data::Shallow(Shallow {
+                    name: delta.name.clone(),
+                })),
                 shallow => shallow.clone(),
             },
         }
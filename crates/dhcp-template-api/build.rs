@@ -1,6 +1,8 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_prost_build::configure()
         .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+        .message_attribute(".", "#[serde(default, deny_unknown_fields)]")
+        .btree_map(".")
         .compile_protos(&["proto/dhcp-template.proto"], &["proto"])?;
 
     Ok(())
@@ -1,17 +1,23 @@
 use std::ops::Deref;
 
-use kube::Client;
+use k8s_openapi::api::core::v1::Node;
+use kube::{Client, runtime::reflector::Store};
 
 use crate::state::State;
 
 pub struct Context {
     client: Client,
     state: State,
+    kubernetes: Store<Node>,
 }
 
-impl From<(Client, State)> for Context {
-    fn from((client, state): (Client, State)) -> Self {
-        Self { client, state }
+impl From<(Client, State, Store<Node>)> for Context {
+    fn from((client, state, kubernetes): (Client, State, Store<Node>)) -> Self {
+        Self {
+            client,
+            state,
+            kubernetes,
+        }
     }
 }
 
@@ -27,4 +33,8 @@ impl Context {
     pub fn client(&self) -> Client {
         self.client.clone()
     }
+
+    pub fn kubernetes(&self) -> &Store<Node> {
+        &self.kubernetes
+    }
 }
@@ -2,13 +2,19 @@ mod context;
 mod plan;
 mod reconcile;
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Result;
 use dhcp_template_crd::DHCPTemplate;
-use futures_util::StreamExt as _;
+use futures_util::{StreamExt as _, future::ready, stream};
+use k8s_openapi::api::core::v1::Node;
 use kube::{
-    Api, Client,
+    Api, Client, ResourceExt as _,
     runtime::{
         Controller, PredicateConfig, WatchStreamExt as _, controller::Config, predicates,
         reflector, watcher,
@@ -21,7 +27,7 @@ use crate::{
         context::Context,
         reconcile::{error_policy, reconcile},
     },
-    state::State,
+    state::{KubernetesMetadata, State},
 };
 
 pub async fn run(state: State) -> Result<()> {
@@ -37,7 +43,24 @@ pub async fn run(state: State) -> Result<()> {
         .applied_objects()
         .predicate_filter(predicates::generation, PredicateConfig::default());
 
-    let ctx = Arc::new(Context::from((client, state)));
+    let (nodes, nodes_writer) = reflector::store();
+    let node_changes = watcher(Api::<Node>::all(client.clone()), watcher::Config::default())
+        .default_backoff()
+        .reflect(nodes_writer)
+        .scan(HashMap::new(), |hashes, event| {
+            let changed = match event {
+                Ok(event) => node_changed(hashes, event),
+                Err(err) => {
+                    warn!("Could not watch nodes: {err}");
+                    false
+                }
+            };
+
+            ready(Some(changed))
+        })
+        .filter_map(|changed| ready(changed.then_some(())));
+
+    let ctx = Arc::new(Context::from((client, state, nodes)));
 
     Controller::for_stream(stream, reader)
         .with_config(
@@ -45,7 +68,7 @@ pub async fn run(state: State) -> Result<()> {
                 .concurrency(1)
                 .debounce(Duration::from_secs(10)),
         )
-        .reconcile_all_on(state_changes)
+        .reconcile_all_on(stream::select(state_changes, node_changes))
         .run(reconcile, error_policy, ctx)
         .for_each(|res| async move {
             if let Err(err) = res {
@@ -56,3 +79,37 @@ pub async fn run(state: State) -> Result<()> {
 
     Ok(())
 }
+
+// Nodes update their status regularly, so only changes of the metadata passed to templates
+// trigger a reconciliation, as well as deleted nodes. A relist triggers a single reconciliation
+// once it is done, which covers nodes deleted while the watch was interrupted.
+fn node_changed(hashes: &mut HashMap<String, u64>, event: watcher::Event<Node>) -> bool {
+    match event {
+        watcher::Event::Apply(node) => {
+            let hash = metadata(&node);
+            hashes.insert(node.name_any(), hash) != Some(hash)
+        }
+        watcher::Event::InitApply(node) => {
+            hashes.insert(node.name_any(), metadata(&node));
+            false
+        }
+        watcher::Event::Delete(node) => {
+            hashes.remove(&node.name_any());
+            true
+        }
+        watcher::Event::Init => {
+            hashes.clear();
+            false
+        }
+        watcher::Event::InitDone => true,
+    }
+}
+
+fn metadata(node: &Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(&KubernetesMetadata::from(node))
+        .ok()
+        .hash(&mut hasher);
+
+    hasher.finish()
+}
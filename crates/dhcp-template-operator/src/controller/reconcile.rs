@@ -35,7 +35,7 @@ pub async fn reconcile(
     ctx: Arc<Context>,
 ) -> Result<Action, ReconcileError> {
     let api: Api<DHCPTemplate> = Api::all(ctx.client());
-    let nodes = ctx.snapshot();
+    let nodes = ctx.snapshot(ctx.kubernetes());
 
     if nodes.is_empty() {
         info!("Skipping reconciliation, because no nodes have been registered yet.");
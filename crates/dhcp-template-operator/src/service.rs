@@ -1,9 +1,17 @@
-use dhcp_template_api::{Refresh, Scope, Update, controller_service_server, update::Data};
-use tonic::{Request, Response, Status};
-use tracing::{Level, instrument};
+use std::pin::pin;
+
+use dhcp_template_api::{
+    Deregistered, Refresh, Scope, Shallow, Update, controller_service_server, update::Data,
+};
+use futures_util::StreamExt as _;
+use tokio::{select, sync::mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{Level, info, instrument, warn};
 
 use crate::state::{self, State};
 
+#[derive(Clone)]
 pub struct ControllerService {
     state: State,
 }
@@ -25,14 +33,12 @@ impl ControllerService {
         let status = match &update.data {
             Some(Data::Full(node)) => self.state.insert((node, update.token)).await,
             Some(Data::Shallow(shallow)) => self.state.status((shallow, update.token)).await,
+            Some(Data::Delta(delta)) => self.state.patch((delta, update.token)).await,
             None => state::Status::Unknown,
         };
 
         let refresh = match status {
-            state::Status::Deprecated | state::Status::Unknown => Refresh {
-                backoff_seconds: Default::default(),
-                scope: Scope::Full.into(),
-            },
+            state::Status::Deprecated | state::Status::Unknown => full_refresh(),
             state::Status::Ok(backoff_seconds) => Refresh {
                 backoff_seconds,
                 scope: Scope::Shallow.into(),
@@ -41,14 +47,106 @@ impl ControllerService {
 
         Ok(refresh)
     }
+
+    #[instrument(skip_all)]
+    async fn session(
+        self,
+        mut updates: Streaming<Update>,
+        refreshes: mpsc::Sender<Result<Refresh, Status>>,
+    ) {
+        let mut changes = pin!(self.state.changes());
+        let mut node: Option<(Shallow, u64)> = None;
+
+        loop {
+            let refresh = select! {
+                update = updates.message() => match update {
+                    Ok(Some(update)) => {
+                        node = shallow(&update).map(|shallow| (shallow, update.token));
+                        self.push_node(update).await
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        warn!("Session failed: {err}");
+                        break;
+                    }
+                },
+
+                Some(()) = changes.next() => {
+                    // The node may have been evicted or removed by another session in the meantime.
+                    match &node {
+                        Some((shallow, token)) => match self.state.status((shallow, *token)).await {
+                            state::Status::Unknown => Ok(full_refresh()),
+                            _ => continue,
+                        },
+                        None => continue,
+                    }
+                }
+            };
+
+            if refreshes.send(refresh).await.is_err() {
+                break;
+            }
+        }
+
+        if let Some((shallow, _)) = node {
+            info!(node = shallow.name, "Session closed, releasing node.");
+            self.state.release(&shallow.name).await;
+        }
+    }
+}
+
+fn full_refresh() -> Refresh {
+    Refresh {
+        backoff_seconds: Default::default(),
+        scope: Scope::Full.into(),
+    }
+}
+
+fn shallow(update: &Update) -> Option<Shallow> {
+    match &update.data {
+        Some(Data::Full(node)) => Some(Shallow {
+            name: node.name.clone(),
+        }),
+        Some(Data::Shallow(shallow)) => Some(shallow.clone()),
+        Some(Data::Delta(delta)) => Some(Shallow {
+            name: delta.name.clone(),
+        }),
+        None => None,
+    }
 }
 
 #[async_trait::async_trait]
 impl controller_service_server::ControllerService for ControllerService {
+    type NodeSessionStream = ReceiverStream<Result<Refresh, Status>>;
+
     async fn push_node(&self, request: Request<Update>) -> Result<Response<Refresh>, Status> {
         let update = request.into_inner();
         let refresh = self.push_node(update).await?;
 
         Ok(refresh.into())
     }
+
+    async fn node_session(
+        &self,
+        request: Request<Streaming<Update>>,
+    ) -> Result<Response<Self::NodeSessionStream>, Status> {
+        let updates = request.into_inner();
+        let (sender, receiver) = mpsc::channel(1);
+
+        tokio::spawn(self.clone().session(updates, sender));
+
+        Ok(ReceiverStream::new(receiver).into())
+    }
+
+    #[instrument(skip_all, fields(node = request.get_ref().name))]
+    async fn deregister(
+        &self,
+        request: Request<Shallow>,
+    ) -> Result<Response<Deregistered>, Status> {
+        let shallow = request.into_inner();
+        info!("Deregistering node.");
+        self.state.remove(&shallow.name).await;
+
+        Ok(Deregistered {}.into())
+    }
 }
@@ -1,23 +1,36 @@
-use std::{cmp::max, sync::Arc, time::Duration};
+use std::{cmp::max, collections::BTreeMap, sync::Arc, time::Duration};
 
-use dhcp_template_api::{Node, Shallow};
+use dhcp_template_api::{Delta, Node, Shallow};
 use envconfig::Envconfig;
 use futures_util::{Stream, StreamExt as _};
 use itertools::Itertools as _;
-use moka::future::Cache;
-use tokio::sync::broadcast;
+use k8s_openapi::api::core::v1::{Node as KubernetesNode, NodeAddress, Taint};
+use kube::runtime::reflector::{ObjectRef, Store};
+use moka::{future::Cache, notification::RemovalCause};
+use serde::Serialize;
+use tokio::{
+    sync::broadcast,
+    time::{Instant, sleep},
+};
 use tokio_stream::wrappers::BroadcastStream;
-use tracing::{Level, error, instrument};
+use tracing::{Level, debug, error, instrument};
 
 #[derive(Debug, Envconfig)]
 pub struct Config {
     #[envconfig(from = "DHCP_TEMPLATE__STATE_IDLE_SECONDS", default = "60")]
     idle_seconds: u64,
+
+    #[envconfig(from = "DHCP_TEMPLATE__STATE_GRACE_SECONDS", default = "30")]
+    grace_seconds: u64,
 }
 
 #[derive(Clone)]
 pub struct State {
     nodes: Cache<String, (Arc<Node>, u64)>,
+    // Nodes whose agent has left, by the time it left. They are kept for the grace period, so a
+    // restarted agent does not remove and add its node again.
+    departures: Cache<String, Instant>,
+    grace: Duration,
     refresh_seconds: u64,
     notifier: broadcast::Sender<()>,
 }
@@ -29,7 +42,12 @@ impl From<Config> for State {
 
         let nodes = Cache::builder()
             .time_to_idle(Duration::from_secs(config.idle_seconds))
-            .eviction_listener(move |_key, _value, _cause| {
+            .eviction_listener(move |_key, _value, cause| {
+                // Replaced nodes are notified by `insert`, if their content changed.
+                if cause == RemovalCause::Replaced {
+                    return;
+                }
+
                 if let Err(err) = eviction.send(()) {
                     error!("Could not send state eviction event: {err}.");
                 }
@@ -40,12 +58,49 @@ impl From<Config> for State {
 
         Self {
             nodes,
+            departures: Cache::new(u64::MAX),
+            grace: Duration::from_secs(config.grace_seconds),
             refresh_seconds,
             notifier,
         }
     }
 }
 
+// A node as passed to templates, joined with the kubernetes node of the same name.
+#[derive(Debug, Serialize)]
+pub struct NodeContext {
+    #[serde(flatten)]
+    node: Arc<Node>,
+    kubernetes: Option<KubernetesMetadata>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KubernetesMetadata {
+    labels: BTreeMap<String, String>,
+    annotations: BTreeMap<String, String>,
+    addresses: Vec<NodeAddress>,
+    taints: Vec<Taint>,
+}
+
+impl From<&KubernetesNode> for KubernetesMetadata {
+    fn from(node: &KubernetesNode) -> Self {
+        Self {
+            labels: node.metadata.labels.clone().unwrap_or_default(),
+            annotations: node.metadata.annotations.clone().unwrap_or_default(),
+            addresses: node
+                .status
+                .as_ref()
+                .and_then(|status| status.addresses.clone())
+                .unwrap_or_default(),
+            taints: node
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.taints.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Status {
     Ok(u64),
@@ -56,6 +111,8 @@ pub enum Status {
 impl State {
     #[instrument(skip_all, fields(node = node.name), ret(level = Level::DEBUG))]
     pub async fn status(&self, (node, token): (&Shallow, u64)) -> Status {
+        self.departures.invalidate(&node.name).await;
+
         match self.nodes.get(&node.name).await {
             Some((_, cached_token)) if cached_token == token => Status::Ok(self.refresh_seconds),
             Some(_) => Status::Deprecated,
@@ -65,22 +122,98 @@ impl State {
 
     #[instrument(skip_all, fields(node = node.name), ret(level = Level::DEBUG))]
     pub async fn insert(&self, (node, token): (&Node, u64)) -> Status {
+        self.departures.invalidate(&node.name).await;
+
+        let unchanged = self
+            .nodes
+            .get(&node.name)
+            .await
+            .is_some_and(|(cached, _)| cached.as_ref() == node);
+
         self.nodes
             .insert(node.name.clone(), (Arc::new(node.clone()), token))
             .await;
 
-        if let Err(err) = self.notifier.send(()) {
+        if unchanged {
+            debug!("Node is unchanged.");
+        } else if let Err(err) = self.notifier.send(()) {
             error!("Could not send state change event: {err}.");
         }
 
         Status::Ok(self.refresh_seconds)
     }
 
-    pub fn snapshot(&self) -> Vec<Arc<Node>> {
+    // Applies the delta to the node, if it is still in the state the delta is based on.
+    #[instrument(skip_all, fields(node = delta.name), ret(level = Level::DEBUG))]
+    pub async fn patch(&self, (delta, token): (&Delta, u64)) -> Status {
+        self.departures.invalidate(&delta.name).await;
+
+        let node = match self.nodes.get(&delta.name).await {
+            Some((node, cached_token)) if cached_token == delta.base_token => node,
+            Some(_) => return Status::Deprecated,
+            None => return Status::Unknown,
+        };
+
+        let mut node = node.as_ref().clone();
+        node.interfaces
+            .retain(|interface| !delta.removed.contains(&interface.name));
+        node.served.clone_from(&delta.served);
+
+        for changed in &delta.interfaces {
+            match node
+                .interfaces
+                .iter_mut()
+                .find(|interface| interface.name == changed.name)
+            {
+                Some(interface) => interface.clone_from(changed),
+                None => node.interfaces.push(changed.clone()),
+            }
+        }
+
+        // Agents push their interfaces by name, so the patched node equals the full node.
+        node.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.insert((&node, token)).await
+    }
+
+    // Removing a node notifies through the eviction listener.
+    #[instrument(skip(self))]
+    pub async fn remove(&self, name: &str) {
+        self.departures.invalidate(name).await;
+        self.nodes.invalidate(name).await;
+    }
+
+    // Removes the node after the grace period, unless its agent pushes again in the meantime.
+    #[instrument(skip(self))]
+    pub async fn release(&self, name: &str) {
+        let departed_at = Instant::now();
+        self.departures.insert(name.to_owned(), departed_at).await;
+
+        let state = self.clone();
+        let name = name.to_owned();
+
+        tokio::spawn(async move {
+            sleep(state.grace).await;
+
+            if state.departures.get(&name).await == Some(departed_at) {
+                debug!(node = name, "Grace period has passed, removing node.");
+                state.departures.invalidate(&name).await;
+                state.nodes.invalidate(&name).await;
+            }
+        });
+    }
+
+    pub fn snapshot(&self, kubernetes: &Store<KubernetesNode>) -> Vec<NodeContext> {
         self.nodes
             .iter()
             .map(|(_, (node, _))| node)
             .sorted_by(|a, b| a.name.cmp(&b.name))
+            .map(|node| NodeContext {
+                kubernetes: kubernetes
+                    .get(&ObjectRef::new(&node.name))
+                    .map(|kubernetes| KubernetesMetadata::from(kubernetes.as_ref())),
+                node,
+            })
             .collect()
     }
 
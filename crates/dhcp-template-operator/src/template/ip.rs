@@ -0,0 +1,192 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::Arc,
+};
+
+use minijinja::{
+    Error, ErrorKind, State,
+    value::{DynObject, Object, ObjectRepr, Value},
+};
+
+// The `ip` filter, which turns a string like `prefix.ip` into an `Ip` object. Other strings of the
+// context are left untouched.
+pub fn ip(value: &Value) -> Result<Value, Error> {
+    if value.downcast_object_ref::<Ip>().is_some() {
+        return Ok(value.clone());
+    }
+
+    let ip = value
+        .as_str()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!("{value} is not an ip address"),
+            )
+        })?;
+
+    Ok(Value::from_object(Ip(ip)))
+}
+
+// An ip address, which renders like the original string, but can be compared, sorted and tested
+// for its family and scope. Methods and the length are those of the string.
+#[derive(Debug)]
+pub struct Ip(IpAddr);
+
+impl Object for Ip {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        let value = match key.as_str()? {
+            "version" => Value::from(if self.0.is_ipv4() { 4 } else { 6 }),
+            "is_global" => Value::from(is_global(self.0)),
+            "is_ula" => Value::from(matches!(self.0, IpAddr::V6(ip) if ip.is_unique_local())),
+            "is_link_local" => Value::from(match self.0 {
+                IpAddr::V4(ip) => ip.is_link_local(),
+                IpAddr::V6(ip) => ip.is_unicast_link_local(),
+            }),
+            "is_loopback" => Value::from(self.0.is_loopback()),
+            "compressed" => Value::from(self.0.to_string()),
+            "exploded" => Value::from(exploded(self.0)),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn enumerator_len(self: &Arc<Self>) -> Option<usize> {
+        Some(self.0.to_string().len())
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        state: &State<'_, '_>,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, Error> {
+        Value::from(self.0.to_string()).call_method(state, method, args)
+    }
+
+    fn custom_cmp(self: &Arc<Self>, other: &DynObject) -> Option<Ordering> {
+        let other = other.downcast_ref::<Self>()?;
+        Some(self.0.cmp(&other.0))
+    }
+
+    fn render(self: &Arc<Self>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+fn exploded(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => ip
+            .segments()
+            .iter()
+            .map(|segment| format!("{segment:04x}"))
+            .collect::<Vec<_>>()
+            .join(":"),
+    }
+}
+
+// `IpAddr::is_global` is not stable yet, so this covers the special purpose ranges relevant for
+// leases: private, shared, loopback, link local, documentation and unique local addresses.
+fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_global_v4(ip),
+        IpAddr::V6(ip) => is_global_v6(ip),
+    }
+}
+
+fn is_global_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_documentation()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 198 && (b & 0xfe) == 18)
+        || a >= 240)
+}
+
+fn is_global_v6(ip: Ipv6Addr) -> bool {
+    let [a, b, ..] = ip.segments();
+
+    // Only global unicast addresses in 2000::/3 without the documentation prefix 2001:db8::/32.
+    (a & 0xe000) == 0x2000 && !(a == 0x2001 && b == 0x0db8)
+}
+
+#[cfg(test)]
+mod tests {
+    use minijinja::{Environment, Error, context};
+
+    fn render(template: &str) -> Result<String, Error> {
+        let mut environment = Environment::new();
+        environment.add_filter("ip", super::ip);
+
+        environment.render_str(
+            template,
+            context!(ips => ["2001:db8::2", "192.0.2.1", "2001:0db8::1"]),
+        )
+    }
+
+    #[test]
+    fn leaves_strings_untouched() -> Result<(), Error> {
+        assert_eq!(
+            render("{{ ips[1] == '192.0.2.1' }} {{ ips[1] | length }}")?,
+            "True 9"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn renders_like_the_string() -> Result<(), Error> {
+        assert_eq!(render("{{ ips[1] | ip }}")?, "192.0.2.1");
+        assert_eq!(render("{{ ips[1] | ip | length }}")?, "9");
+        assert_eq!(render("{{ '192.0' in ips[1] | ip | string }}")?, "True");
+
+        Ok(())
+    }
+
+    #[test]
+    fn compares_addresses() -> Result<(), Error> {
+        assert_eq!(
+            render("{{ ips | map('ip') | sort | join(' ') }}")?,
+            "192.0.2.1 2001:db8::1 2001:db8::2"
+        );
+        assert_eq!(render("{{ ips[2] | ip == '2001:db8::1' | ip }}")?, "True");
+
+        Ok(())
+    }
+
+    #[test]
+    fn exposes_attributes() -> Result<(), Error> {
+        let ip = "{% set ip = ips[2] | ip %}";
+
+        assert_eq!(
+            render(&format!("{ip}{{{{ ip.version }}}} {{{{ ip.is_global }}}}"))?,
+            "6 False"
+        );
+        assert_eq!(
+            render(&format!("{ip}{{{{ ip.exploded }}}}"))?,
+            "2001:0db8:0000:0000:0000:0000:0000:0001"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_strings() {
+        assert!(render("{{ 'eth0' | ip }}").is_err());
+    }
+}
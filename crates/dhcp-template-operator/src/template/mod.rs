@@ -1,13 +1,14 @@
-use std::sync::Arc;
+mod ip;
 
-use dhcp_template_api::Node;
 use dhcp_template_crd::DHCPTemplateSpec;
 use kube::api::DynamicObject;
-use minijinja::{Environment, context};
+use minijinja::{Environment, Value, context};
 use serde::Deserialize;
 use serde_yaml::Deserializer;
 use tracing::{Level, instrument};
 
+use crate::state::NodeContext;
+
 #[derive(Debug, thiserror::Error)]
 pub enum TemplateError {
     #[error("Could not render template: {0}")]
@@ -21,11 +22,14 @@ pub trait ManifestTemplate<D> {
     fn render(&self, data: D) -> Result<Vec<DynamicObject>, TemplateError>;
 }
 
-impl ManifestTemplate<Vec<Arc<Node>>> for DHCPTemplateSpec {
+impl ManifestTemplate<Vec<NodeContext>> for DHCPTemplateSpec {
     #[instrument(skip_all, ret(level = Level::DEBUG), err(level = Level::WARN))]
-    fn render(&self, data: Vec<Arc<Node>>) -> Result<Vec<DynamicObject>, TemplateError> {
-        let environment = Environment::new();
-        let manifests = environment.render_str(&self.template, context!(nodes => data))?;
+    fn render(&self, data: Vec<NodeContext>) -> Result<Vec<DynamicObject>, TemplateError> {
+        let mut environment = Environment::new();
+        environment.add_filter("ip", ip::ip);
+
+        let nodes = Value::from_serialize(&data);
+        let manifests = environment.render_str(&self.template, context!(nodes))?;
 
         let objects: Vec<Option<DynamicObject>> = Deserializer::from_str(&manifests)
             .map(Option::<DynamicObject>::deserialize)